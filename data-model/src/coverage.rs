@@ -0,0 +1,115 @@
+//! Byte coverage of struct models: finds the bytes that no field describes.
+
+use crate::{ModelName, StructModel, TypeModel};
+
+/// A range of bytes inside a struct that is not covered by any field, i.e. a gap
+/// between fields (usually from `#[loc]`) or padding at the end of the struct.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnmappedRange {
+    /// The path to the parent struct, from the root struct.
+    ///
+    /// Array items are written as `field[]`, as the range is repeated for each item.
+    /// The path is empty if the parent is the root struct.
+    pub path: String,
+    /// The name of the parent struct
+    pub parent: ModelName,
+    /// Offset of the range, relative to the start of the parent struct
+    pub relative_offset: usize,
+    /// Size of the range, in bytes
+    pub size: usize,
+    /// The absolute offset of every instance of the range, one for each
+    /// array item that contains it.
+    pub offsets: Vec<usize>,
+}
+
+impl StructModel {
+    /// Lists the byte ranges of the struct that are not described by any field,
+    /// including ranges inside nested structs.
+    ///
+    /// Ranges are sorted by the absolute offset of their first instance.
+    pub fn unmapped_ranges(&self) -> Vec<UnmappedRange> {
+        let mut ranges = Vec::new();
+        collect_struct(self, String::new(), &[0], &mut ranges);
+        ranges.sort_by_key(|r| r.offsets.first().copied());
+        ranges
+    }
+}
+
+impl UnmappedRange {
+    /// Returns the absolute offsets of the instances of this range that
+    /// contain at least one non-zero byte.
+    ///
+    /// Instances that are out of bounds for `bytes` are ignored.
+    pub fn non_zero_offsets<'a>(&'a self, bytes: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        self.offsets.iter().copied().filter(move |&offset| {
+            bytes
+                .get(offset..offset + self.size)
+                .is_some_and(|range| range.iter().any(|&b| b != 0))
+        })
+    }
+
+    /// Returns whether any instance of this range contains a non-zero byte.
+    pub fn is_non_zero(&self, bytes: &[u8]) -> bool {
+        self.non_zero_offsets(bytes).next().is_some()
+    }
+
+    /// Total size of all instances of the range, in bytes.
+    pub fn total_size(&self) -> usize {
+        self.size * self.offsets.len()
+    }
+}
+
+/// Collects unmapped ranges for a struct located at each of `bases`.
+fn collect_struct(
+    model: &StructModel,
+    path: String,
+    bases: &[usize],
+    ranges: &mut Vec<UnmappedRange>,
+) {
+    let mut add_gap = |start: usize, end: usize| {
+        if end > start {
+            ranges.push(UnmappedRange {
+                path: path.clone(),
+                parent: model.name,
+                relative_offset: start,
+                size: end - start,
+                offsets: bases.iter().map(|b| b + start).collect(),
+            });
+        }
+    };
+
+    let mut offset = 0;
+    for field in &model.fields {
+        add_gap(offset, field.offset);
+        offset = offset.max(field.offset + field.size);
+    }
+    add_gap(offset, model.total_len);
+
+    for field in &model.fields {
+        let field_path = match path.as_str() {
+            "" => field.name.to_string(),
+            parent => format!("{parent}.{}", field.name),
+        };
+        let field_bases: Vec<_> = bases.iter().map(|b| b + field.offset).collect();
+        collect_type(&field.type_model, field_path, &field_bases, ranges);
+    }
+}
+
+fn collect_type(model: &TypeModel, path: String, bases: &[usize], ranges: &mut Vec<UnmappedRange>) {
+    // Dynamic arrays may use all of their capacity, so every slot is considered
+    let (item, len) = match model {
+        TypeModel::Struct(struct_model) => {
+            return collect_struct(struct_model, path, bases, ranges)
+        }
+        TypeModel::Flags(flags) => return collect_type(&flags.storage, path, bases, ranges),
+        TypeModel::Array(array) => (&array.0, array.1),
+        TypeModel::Vec(vec) => (&vec.item, vec.capacity),
+        _ => return,
+    };
+    let item_size = item.size();
+    let item_bases: Vec<_> = bases
+        .iter()
+        .flat_map(|b| (0..len).map(move |i| b + i * item_size))
+        .collect();
+    collect_type(item, format!("{path}[]"), &item_bases, ranges);
+}
@@ -0,0 +1,585 @@
+//! Reflective decoding and encoding, using only the data model.
+
+use std::fmt::Display;
+
+use crate::{BitFlagsModel, ModelName, StringModel, TypeModel, VecModel};
+
+/// A value decoded from its binary representation, by following its [`TypeModel`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DynamicValue {
+    Empty,
+    /// Struct fields, in declaration order
+    Struct(Vec<(ModelName, DynamicValue)>),
+    /// Array items. For dynamic arrays, only items up to the current length are included.
+    Array(Vec<DynamicValue>),
+    Primitive(PrimitiveValue),
+    /// Strings are decoded lossily, so invalid UTF-8 sequences are replaced.
+    String(String),
+}
+
+/// A value of a Rust primitive type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrimitiveValue {
+    Bool(bool),
+    U8(u8),
+    I8(i8),
+    U16(u16),
+    I16(i16),
+    U32(u32),
+    I32(i32),
+    U64(u64),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DynamicError {
+    /// The byte buffer is smaller than the size of the model.
+    BufferTooSmall { expected: usize, found: usize },
+    /// The model contains a primitive type that is not supported.
+    UnknownPrimitive(ModelName),
+    /// The value does not have the structure described by the model.
+    TypeMismatch { expected: String, found: String },
+    /// The path could not be parsed.
+    InvalidPath(String),
+    /// A struct in the path does not have a field with the given name.
+    UnknownField(String),
+    /// An array index in the path is out of bounds.
+    IndexOutOfBounds { index: usize, len: usize },
+    /// The value is too long for a dynamic array or string.
+    CapacityExceeded { len: usize, capacity: usize },
+}
+
+impl TypeModel {
+    /// Returns the size of the type in its binary representation.
+    ///
+    /// ## Panics
+    /// Panics if the model contains an unknown primitive type.
+    pub fn size(&self) -> usize {
+        match self {
+            TypeModel::Empty => 0,
+            TypeModel::Struct(model) => model.total_len,
+            TypeModel::Array(array) => array.0.size() * array.1,
+            TypeModel::Primitive(name) => {
+                PrimitiveValue::size_of(name).unwrap_or_else(|| panic!("unknown primitive {name}"))
+            }
+            TypeModel::Enum(model) => PrimitiveValue::size_of(model.repr)
+                .unwrap_or_else(|| panic!("unknown primitive {}", model.repr)),
+            TypeModel::Flags(model) => model.storage.size(),
+            TypeModel::Vec(model) => model.item.size() * model.capacity + len_size(model.len_type),
+            TypeModel::String(model) => model.capacity + model.len_type.map_or(0, len_size),
+            TypeModel::BitFlags(model) => model.words * 4,
+        }
+    }
+
+    /// Returns the name of the primitive type that stores values of this type, if any.
+    ///
+    /// For enums, this is the type of the discriminant. For flags, it is the type
+    /// of their storage, if primitive.
+    pub fn primitive_type(&self) -> Option<ModelName> {
+        match self {
+            TypeModel::Primitive(name) => Some(name),
+            TypeModel::Enum(model) => Some(model.repr),
+            TypeModel::Flags(model) => model.storage.primitive_type(),
+            _ => None,
+        }
+    }
+
+    /// Decodes a value from the start of the byte buffer.
+    pub fn decode(&self, bytes: &[u8]) -> Result<DynamicValue, DynamicError> {
+        let size = self.size();
+        if bytes.len() < size {
+            return Err(DynamicError::BufferTooSmall {
+                expected: size,
+                found: bytes.len(),
+            });
+        }
+        self.decode_unchecked(&bytes[..size])
+    }
+
+    fn decode_unchecked(&self, bytes: &[u8]) -> Result<DynamicValue, DynamicError> {
+        Ok(match self {
+            TypeModel::Empty => DynamicValue::Empty,
+            TypeModel::Flags(model) => model.storage.decode_unchecked(bytes)?,
+            TypeModel::Struct(model) => DynamicValue::Struct(
+                model
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        let bytes = &bytes[field.offset..field.offset + field.size];
+                        Ok((field.name, field.type_model.decode_unchecked(bytes)?))
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            TypeModel::Array(array) => {
+                let (item, len) = array.as_ref();
+                let item_size = item.size();
+                DynamicValue::Array(
+                    (0..*len)
+                        .map(|i| item.decode_unchecked(&bytes[i * item_size..(i + 1) * item_size]))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            // Enums are decoded as their discriminant
+            TypeModel::Primitive(_) | TypeModel::Enum(_) => {
+                let name = self.primitive_type().unwrap();
+                DynamicValue::Primitive(PrimitiveValue::read(name, bytes)?)
+            }
+            TypeModel::Vec(model) => decode_vec(model, bytes)?,
+            TypeModel::String(model) => decode_string(model, bytes)?,
+            TypeModel::BitFlags(model) => decode_bit_flags(model, bytes),
+        })
+    }
+
+    /// Finds the value at the given path, e.g. `characters[0].level`.
+    ///
+    /// Paths are made of struct field names separated by `.`, each optionally
+    /// followed by one or more array indices.
+    ///
+    /// Returns the value's offset from the start of this type, along with its model.
+    ///
+    /// Only the layout is known here, so indices into dynamic arrays are checked against
+    /// their capacity. To also check them against the current length, use
+    /// [`find_path_in`](TypeModel::find_path_in).
+    pub fn find_path(&self, path: &str) -> Result<(usize, &TypeModel), DynamicError> {
+        self.find_path_impl(path, None)
+    }
+
+    /// Like [`find_path`](TypeModel::find_path), but indices into dynamic arrays
+    /// must be lower than the array length, which is decoded from the byte buffer.
+    pub fn find_path_in(
+        &self,
+        path: &str,
+        bytes: &[u8],
+    ) -> Result<(usize, &TypeModel), DynamicError> {
+        let size = self.size();
+        if bytes.len() < size {
+            return Err(DynamicError::BufferTooSmall {
+                expected: size,
+                found: bytes.len(),
+            });
+        }
+        self.find_path_impl(path, Some(&bytes[..size]))
+    }
+
+    fn find_path_impl(
+        &self,
+        path: &str,
+        bytes: Option<&[u8]>,
+    ) -> Result<(usize, &TypeModel), DynamicError> {
+        let mut model = self;
+        let mut offset = 0;
+        for segment in path.split('.') {
+            let (name, mut indices) = segment.split_at(segment.find('[').unwrap_or(segment.len()));
+            // Flag names are not part of the path, only their storage is
+            if let TypeModel::Flags(flags) = model {
+                model = &flags.storage;
+            }
+            let TypeModel::Struct(struct_model) = model else {
+                return Err(DynamicError::TypeMismatch {
+                    expected: "struct".to_string(),
+                    found: model.to_string(),
+                });
+            };
+            let field = struct_model
+                .fields
+                .iter()
+                .find(|f| f.name == name)
+                .ok_or_else(|| DynamicError::UnknownField(name.to_string()))?;
+            offset += field.offset;
+            model = &field.type_model;
+
+            while !indices.is_empty() {
+                let (index, rest) = indices
+                    .strip_prefix('[')
+                    .and_then(|s| s.split_once(']'))
+                    .and_then(|(i, rest)| Some((i.parse::<usize>().ok()?, rest)))
+                    .ok_or_else(|| DynamicError::InvalidPath(path.to_string()))?;
+                // Without data, dynamic arrays are indexed up to their capacity
+                let (item, len) = match model {
+                    TypeModel::Array(array) => (&array.0, array.1),
+                    TypeModel::Vec(vec) => match bytes {
+                        Some(bytes) => {
+                            let len_offset = offset + vec.item.size() * vec.capacity;
+                            let len = read_len(vec.len_type, &bytes[len_offset..])?;
+                            (&vec.item, len.min(vec.capacity))
+                        }
+                        None => (&vec.item, vec.capacity),
+                    },
+                    _ => {
+                        return Err(DynamicError::TypeMismatch {
+                            expected: "array".to_string(),
+                            found: model.to_string(),
+                        })
+                    }
+                };
+                if index >= len {
+                    return Err(DynamicError::IndexOutOfBounds { index, len });
+                }
+                offset += index * item.size();
+                model = item;
+                indices = rest;
+            }
+        }
+        Ok((offset, model))
+    }
+}
+
+impl DynamicValue {
+    /// Creates an array of `u8` values.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self::Array(
+            bytes
+                .iter()
+                .map(|&b| Self::Primitive(PrimitiveValue::U8(b)))
+                .collect(),
+        )
+    }
+
+    /// Encodes the value into the start of the byte buffer, using the given model.
+    ///
+    /// Only bytes that belong to fields in the model are written, so unknown regions
+    /// in the buffer are left untouched.
+    ///
+    /// If the value does not match the model, the buffer may be left partially written.
+    pub fn encode(&self, model: &TypeModel, bytes: &mut [u8]) -> Result<(), DynamicError> {
+        let size = model.size();
+        if bytes.len() < size {
+            return Err(DynamicError::BufferTooSmall {
+                expected: size,
+                found: bytes.len(),
+            });
+        }
+        self.encode_unchecked(model, &mut bytes[..size])
+    }
+
+    fn encode_unchecked(&self, model: &TypeModel, bytes: &mut [u8]) -> Result<(), DynamicError> {
+        match (self, model) {
+            (value, TypeModel::Flags(model)) => value.encode_unchecked(&model.storage, bytes),
+            (DynamicValue::Array(values), TypeModel::Vec(model)) => {
+                encode_vec(values, model, bytes)
+            }
+            (DynamicValue::String(value), TypeModel::String(model)) => {
+                encode_string(value, model, bytes)
+            }
+            (DynamicValue::Array(values), TypeModel::BitFlags(model))
+                if values.len() == model.len() =>
+            {
+                encode_bit_flags(values, model, bytes)
+            }
+            (DynamicValue::Empty, TypeModel::Empty) => Ok(()),
+            (DynamicValue::Struct(values), TypeModel::Struct(model))
+                if values.len() == model.fields.len()
+                    && values
+                        .iter()
+                        .zip(&model.fields)
+                        .all(|((name, _), field)| *name == field.name) =>
+            {
+                for ((_, value), field) in values.iter().zip(&model.fields) {
+                    let bytes = &mut bytes[field.offset..field.offset + field.size];
+                    value.encode_unchecked(&field.type_model, bytes)?;
+                }
+                Ok(())
+            }
+            (DynamicValue::Array(values), TypeModel::Array(array)) if values.len() == array.1 => {
+                let item_size = array.0.size();
+                for (i, value) in values.iter().enumerate() {
+                    let bytes = &mut bytes[i * item_size..(i + 1) * item_size];
+                    value.encode_unchecked(&array.0, bytes)?;
+                }
+                Ok(())
+            }
+            (DynamicValue::Primitive(value), TypeModel::Primitive(_) | TypeModel::Enum(_))
+                if Some(value.type_name()) == model.primitive_type() =>
+            {
+                value.write(bytes);
+                Ok(())
+            }
+            (value, model) => Err(DynamicError::TypeMismatch {
+                expected: model.to_string(),
+                found: value.describe(),
+            }),
+        }
+    }
+
+    /// Short description of the value's type, used in error messages.
+    fn describe(&self) -> String {
+        match self {
+            DynamicValue::Empty => "()".to_string(),
+            DynamicValue::Struct(fields) => format!("struct with {} fields", fields.len()),
+            DynamicValue::Array(values) => format!("array of length {}", values.len()),
+            DynamicValue::Primitive(value) => value.type_name().to_string(),
+            DynamicValue::String(value) => format!("string of length {}", value.len()),
+        }
+    }
+}
+
+impl PrimitiveValue {
+    /// Returns the name of the value's Rust type, as found in
+    /// [`TypeModel::Primitive`].
+    pub fn type_name(&self) -> ModelName {
+        match self {
+            PrimitiveValue::Bool(_) => "bool",
+            PrimitiveValue::U8(_) => "u8",
+            PrimitiveValue::I8(_) => "i8",
+            PrimitiveValue::U16(_) => "u16",
+            PrimitiveValue::I16(_) => "i16",
+            PrimitiveValue::U32(_) => "u32",
+            PrimitiveValue::I32(_) => "i32",
+            PrimitiveValue::U64(_) => "u64",
+            PrimitiveValue::I64(_) => "i64",
+            PrimitiveValue::F32(_) => "f32",
+            PrimitiveValue::F64(_) => "f64",
+        }
+    }
+
+    /// Returns the binary size of the primitive type with the given name, or [`None`] if
+    /// the type is not supported.
+    pub fn size_of(type_name: &str) -> Option<usize> {
+        Some(match type_name {
+            "bool" | "u8" | "i8" => 1,
+            "u16" | "i16" => 2,
+            "u32" | "i32" | "f32" => 4,
+            "u64" | "i64" | "f64" => 8,
+            _ => return None,
+        })
+    }
+
+    /// Reads a little-endian value of the primitive type with the given name.
+    ///
+    /// ## Panics
+    /// Panics if the buffer is smaller than the type's size.
+    pub fn read(type_name: ModelName, bytes: &[u8]) -> Result<Self, DynamicError> {
+        macro_rules! read {
+            ($variant:ident, $ty:ty) => {
+                Self::$variant(<$ty>::from_le_bytes(
+                    bytes[..std::mem::size_of::<$ty>()].try_into().unwrap(),
+                ))
+            };
+        }
+        Ok(match type_name {
+            "bool" => Self::Bool(bytes[0] != 0),
+            "u8" => read!(U8, u8),
+            "i8" => read!(I8, i8),
+            "u16" => read!(U16, u16),
+            "i16" => read!(I16, i16),
+            "u32" => read!(U32, u32),
+            "i32" => read!(I32, i32),
+            "u64" => read!(U64, u64),
+            "i64" => read!(I64, i64),
+            "f32" => read!(F32, f32),
+            "f64" => read!(F64, f64),
+            name => return Err(DynamicError::UnknownPrimitive(name)),
+        })
+    }
+
+    /// Writes the value in little-endian byte order.
+    ///
+    /// ## Panics
+    /// Panics if the buffer is smaller than the type's size.
+    pub fn write(&self, bytes: &mut [u8]) {
+        let mut write = |src: &[u8]| bytes[..src.len()].copy_from_slice(src);
+        match *self {
+            PrimitiveValue::Bool(v) => write(&[u8::from(v)]),
+            PrimitiveValue::U8(v) => write(&v.to_le_bytes()),
+            PrimitiveValue::I8(v) => write(&v.to_le_bytes()),
+            PrimitiveValue::U16(v) => write(&v.to_le_bytes()),
+            PrimitiveValue::I16(v) => write(&v.to_le_bytes()),
+            PrimitiveValue::U32(v) => write(&v.to_le_bytes()),
+            PrimitiveValue::I32(v) => write(&v.to_le_bytes()),
+            PrimitiveValue::U64(v) => write(&v.to_le_bytes()),
+            PrimitiveValue::I64(v) => write(&v.to_le_bytes()),
+            PrimitiveValue::F32(v) => write(&v.to_le_bytes()),
+            PrimitiveValue::F64(v) => write(&v.to_le_bytes()),
+        }
+    }
+}
+
+fn len_size(len_type: ModelName) -> usize {
+    PrimitiveValue::size_of(len_type).unwrap_or_else(|| panic!("unknown primitive {len_type}"))
+}
+
+/// Reads a length field of an unsigned integer type.
+fn read_len(len_type: ModelName, bytes: &[u8]) -> Result<usize, DynamicError> {
+    let size = len_size(len_type);
+    let mut buf = [0; 8];
+    buf[..size].copy_from_slice(&bytes[..size]);
+    // Make sure the type is supported
+    PrimitiveValue::read(len_type, &buf)?;
+    Ok(u64::from_le_bytes(buf).try_into().unwrap_or(usize::MAX))
+}
+
+/// Writes a length field of an unsigned integer type.
+fn write_len(len_type: ModelName, len: usize, bytes: &mut [u8]) -> Result<(), DynamicError> {
+    let size = len_size(len_type);
+    let buf = (len as u64).to_le_bytes();
+    PrimitiveValue::read(len_type, &buf)?.write(&mut bytes[..size]);
+    Ok(())
+}
+
+fn decode_vec(model: &VecModel, bytes: &[u8]) -> Result<DynamicValue, DynamicError> {
+    let item_size = model.item.size();
+    let items_len = item_size * model.capacity;
+    let len = read_len(model.len_type, &bytes[items_len..])?.min(model.capacity);
+    Ok(DynamicValue::Array(
+        (0..len)
+            .map(|i| {
+                model
+                    .item
+                    .decode_unchecked(&bytes[i * item_size..(i + 1) * item_size])
+            })
+            .collect::<Result<_, _>>()?,
+    ))
+}
+
+fn encode_vec(
+    values: &[DynamicValue],
+    model: &VecModel,
+    bytes: &mut [u8],
+) -> Result<(), DynamicError> {
+    if values.len() > model.capacity {
+        return Err(DynamicError::CapacityExceeded {
+            len: values.len(),
+            capacity: model.capacity,
+        });
+    }
+    let item_size = model.item.size();
+    for (i, value) in values.iter().enumerate() {
+        value.encode_unchecked(&model.item, &mut bytes[i * item_size..(i + 1) * item_size])?;
+    }
+    write_len(
+        model.len_type,
+        values.len(),
+        &mut bytes[item_size * model.capacity..],
+    )
+}
+
+fn decode_string(model: &StringModel, bytes: &[u8]) -> Result<DynamicValue, DynamicError> {
+    let buf = &bytes[..model.capacity];
+    let len = match model.len_type {
+        Some(len_type) => read_len(len_type, &bytes[model.capacity..])?.min(model.capacity),
+        None => buf.iter().position(|&b| b == 0).unwrap_or(model.capacity),
+    };
+    Ok(DynamicValue::String(
+        String::from_utf8_lossy(&buf[..len]).into_owned(),
+    ))
+}
+
+fn encode_string(value: &str, model: &StringModel, bytes: &mut [u8]) -> Result<(), DynamicError> {
+    let len = value.len();
+    if len > model.capacity {
+        return Err(DynamicError::CapacityExceeded {
+            len,
+            capacity: model.capacity,
+        });
+    }
+    bytes[..len].copy_from_slice(value.as_bytes());
+    match model.len_type {
+        Some(len_type) => write_len(len_type, len, &mut bytes[model.capacity..])?,
+        // Strings that fill the whole buffer have no terminator
+        None if len < model.capacity => bytes[len] = 0,
+        None => {}
+    }
+    Ok(())
+}
+
+fn decode_bit_flags(model: &BitFlagsModel, bytes: &[u8]) -> DynamicValue {
+    let per_word = u32::BITS as usize / model.bits;
+    let mask = u32::MAX >> (u32::BITS as usize - model.bits);
+    DynamicValue::Array(
+        (0..model.len())
+            .map(|i| {
+                let word = &bytes[i / per_word * 4..][..4];
+                let word = u32::from_le_bytes(word.try_into().unwrap());
+                let value = (word >> (i % per_word * model.bits)) & mask;
+                DynamicValue::Primitive(PrimitiveValue::U32(value))
+            })
+            .collect(),
+    )
+}
+
+fn encode_bit_flags(
+    values: &[DynamicValue],
+    model: &BitFlagsModel,
+    bytes: &mut [u8],
+) -> Result<(), DynamicError> {
+    let per_word = u32::BITS as usize / model.bits;
+    let mask = u32::MAX >> (u32::BITS as usize - model.bits);
+    for (i, value) in values.iter().enumerate() {
+        let value = match value {
+            DynamicValue::Primitive(PrimitiveValue::U32(v)) if *v <= mask => *v,
+            value => {
+                return Err(DynamicError::TypeMismatch {
+                    expected: format!("{}-bit flag", model.bits),
+                    found: value.describe(),
+                })
+            }
+        };
+        let word = &mut bytes[i / per_word * 4..][..4];
+        let shift = i % per_word * model.bits;
+        let old = u32::from_le_bytes((&*word).try_into().unwrap());
+        let new = (old & !(mask << shift)) | (value << shift);
+        word.copy_from_slice(&new.to_le_bytes());
+    }
+    Ok(())
+}
+
+impl Display for TypeModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypeModel::Empty => f.write_str("()"),
+            TypeModel::Struct(model) => f.write_str(model.name),
+            TypeModel::Array(array) => write!(f, "[{}; {}]", array.0, array.1),
+            TypeModel::Primitive(name) => f.write_str(name),
+            TypeModel::Enum(model) => f.write_str(model.name),
+            TypeModel::Flags(model) => write!(f, "Flags<{}>", model.name),
+            TypeModel::Vec(model) => write!(f, "Vec<{}, {}>", model.item, model.capacity),
+            TypeModel::String(model) => write!(f, "String<{}>", model.capacity),
+            TypeModel::BitFlags(model) => write!(f, "BitFlags<{}, {}>", model.bits, model.len()),
+        }
+    }
+}
+
+impl Display for PrimitiveValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrimitiveValue::Bool(v) => v.fmt(f),
+            PrimitiveValue::U8(v) => v.fmt(f),
+            PrimitiveValue::I8(v) => v.fmt(f),
+            PrimitiveValue::U16(v) => v.fmt(f),
+            PrimitiveValue::I16(v) => v.fmt(f),
+            PrimitiveValue::U32(v) => v.fmt(f),
+            PrimitiveValue::I32(v) => v.fmt(f),
+            PrimitiveValue::U64(v) => v.fmt(f),
+            PrimitiveValue::I64(v) => v.fmt(f),
+            PrimitiveValue::F32(v) => v.fmt(f),
+            PrimitiveValue::F64(v) => v.fmt(f),
+        }
+    }
+}
+
+impl Display for DynamicError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DynamicError::BufferTooSmall { expected, found } => write!(
+                f,
+                "buffer too small: expected at least {expected} bytes, found {found}"
+            ),
+            DynamicError::UnknownPrimitive(name) => write!(f, "unknown primitive type {name}"),
+            DynamicError::TypeMismatch { expected, found } => {
+                write!(f, "type mismatch: expected {expected}, found {found}")
+            }
+            DynamicError::InvalidPath(path) => write!(f, "invalid path {path}"),
+            DynamicError::UnknownField(name) => write!(f, "unknown field {name}"),
+            DynamicError::IndexOutOfBounds { index, len } => {
+                write!(f, "index {index} out of bounds for array of length {len}")
+            }
+            DynamicError::CapacityExceeded { len, capacity } => {
+                write!(f, "length {len} exceeds capacity {capacity}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DynamicError {}
@@ -1,6 +1,12 @@
 //! Data model exported by the procedural macros for `SaveBin`.
 
-use std::{any::TypeId, marker::PhantomData};
+use std::{any::TypeId, marker::PhantomData, ops::RangeInclusive};
+
+mod coverage;
+mod dynamic;
+
+pub use coverage::*;
+pub use dynamic::*;
 
 /// Types that have a well-formed data model for the save file format.
 pub trait Model: 'static {
@@ -33,6 +39,74 @@ pub struct FieldModel {
     pub type_model: TypeModel,
     pub offset: usize,
     pub size: usize,
+    pub annotations: FieldAnnotations,
+}
+
+/// Documentation and value constraints for a struct field, taken from its
+/// doc comments and `#[range]`/`#[table]` attributes.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FieldAnnotations {
+    /// The field's doc comment, one line per source line
+    pub doc: Option<&'static str>,
+    /// Known valid values, e.g. `0.0..=100.0` for percentages
+    pub range: Option<RangeInclusive<f64>>,
+    /// The BDAT table that the field's value is an ID for, e.g. `BTL_Pair`
+    pub table: Option<ModelName>,
+}
+
+/// The model for a fieldless enum, which is stored as its discriminant.
+#[derive(Debug)]
+pub struct EnumModel {
+    pub name: ModelName,
+    pub type_id: TypeId,
+    /// The primitive type used to store the discriminant, e.g. `u32`
+    pub repr: ModelName,
+    /// Variant names, along with their discriminants
+    pub variants: Vec<(ModelName, u64)>,
+}
+
+/// The model for a set of named 1-bit flags.
+#[derive(Debug)]
+pub struct FlagsModel {
+    /// The name of the type that names the flags
+    pub name: ModelName,
+    pub type_id: TypeId,
+    /// The type that stores the flags
+    pub storage: TypeModel,
+    /// Flag names, along with their bit index
+    pub flags: Vec<(ModelName, usize)>,
+}
+
+/// The model for a dynamic array with fixed capacity.
+///
+/// Items are stored first, followed by the length.
+#[derive(Debug)]
+pub struct VecModel {
+    pub item: TypeModel,
+    pub capacity: usize,
+    /// The primitive type that stores the length, e.g. `u64`
+    pub len_type: ModelName,
+}
+
+/// The model for a string with fixed storage.
+///
+/// Strings are stored as bytes, optionally followed by the length.
+#[derive(Debug)]
+pub struct StringModel {
+    /// Size of the string storage, in bytes
+    pub capacity: usize,
+    /// The primitive type that stores the length. If this is [`None`], the string
+    /// is nul-terminated instead.
+    pub len_type: Option<ModelName>,
+}
+
+/// The model for an array of packed flags, stored in `u32` words.
+#[derive(Debug)]
+pub struct BitFlagsModel {
+    /// Size of each flag, in bits
+    pub bits: usize,
+    /// Number of `u32` words
+    pub words: usize,
 }
 
 #[derive(Debug)]
@@ -45,6 +119,16 @@ pub enum TypeModel {
     Array(Box<(TypeModel, usize)>),
     /// Rust primitive types
     Primitive(ModelName),
+    /// Fieldless enums
+    Enum(Box<EnumModel>),
+    /// Named flags, stored as bits of another type
+    Flags(Box<FlagsModel>),
+    /// Dynamic arrays with fixed capacity
+    Vec(Box<VecModel>),
+    /// Strings with fixed storage
+    String(Box<StringModel>),
+    /// Arrays of packed N-bit flags
+    BitFlags(Box<BitFlagsModel>),
 }
 
 impl StructModel {
@@ -74,6 +158,33 @@ impl StructModel {
     }
 }
 
+impl EnumModel {
+    /// Returns the name of the variant with the given discriminant, if any.
+    pub fn variant_name(&self, discriminant: u64) -> Option<ModelName> {
+        self.variants
+            .iter()
+            .find(|(_, d)| *d == discriminant)
+            .map(|(name, _)| *name)
+    }
+}
+
+impl BitFlagsModel {
+    /// Returns the number of flags in the array.
+    pub fn len(&self) -> usize {
+        self.words * u32::BITS as usize / self.bits
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl FieldAnnotations {
+    pub fn is_empty(&self) -> bool {
+        self.doc.is_none() && self.range.is_none() && self.table.is_none()
+    }
+}
+
 impl StructModelBuilder {
     pub fn add_field(
         &mut self,
@@ -82,16 +193,26 @@ impl StructModelBuilder {
         offset: usize,
         size: usize,
     ) -> &mut Self {
-        if offset < self.0.total_len {
-            self.0.total_len = offset;
-        }
+        self.add_annotated_field(name, type_model, offset, size, FieldAnnotations::default())
+    }
+
+    pub fn add_annotated_field(
+        &mut self,
+        name: ModelName,
+        type_model: TypeModel,
+        offset: usize,
+        size: usize,
+        annotations: FieldAnnotations,
+    ) -> &mut Self {
         self.0.fields.push(FieldModel {
             name,
             offset,
             type_model,
             size,
+            annotations,
         });
-        self.0.total_len += size;
+        // Fields may be placed after a gap, which is also part of the struct
+        self.0.total_len = self.0.total_len.max(offset + size);
         self
     }
 
@@ -0,0 +1,213 @@
+//! Structured comparison between two save (or system) files.
+//!
+//! Changes are found by comparing the binary representation of both files, and are then
+//! named after the fields in the data model. For example, a changed character level is
+//! reported as `characters[3].level`, and a changed 1-bit flag as `flags.flags_1b[4521]`.
+//!
+//! ```ignore
+//! use recordkeeper::{diff, SaveFile};
+//!
+//! let before = SaveFile::from_bytes(&before_bytes)?;
+//! let after = SaveFile::from_bytes(&after_bytes)?;
+//! for change in diff::diff(before.save(), after.save())? {
+//!     println!("{change}");
+//! }
+//! ```
+
+use std::fmt::Display;
+
+use byteorder::{ByteOrder, LittleEndian};
+use recordkeeper_data_model::{BitFlagsModel, Model, PrimitiveValue, StructModel, TypeModel};
+
+use crate::{error::SaveError, io::SaveBin, SaveResult};
+
+/// A value that differs between two files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// Path to the changed value, e.g. `characters[3].class_inventory[12].level`.
+    ///
+    /// Bytes that are not part of any known field are named after their offset
+    /// in the parent structure, e.g. `dlc4@0x1c`.
+    pub path: String,
+    /// Offset of the value from the start of the file
+    pub offset: usize,
+    pub old: PrimitiveValue,
+    pub new: PrimitiveValue,
+}
+
+struct Differ<'a> {
+    old: &'a [u8],
+    new: &'a [u8],
+    changes: Vec<Change>,
+}
+
+/// Compares two save or system files, and returns the values that changed, sorted by
+/// offset.
+///
+/// Only known fields are compared, use [`diff_bytes`] to also include unknown regions.
+pub fn diff<T>(old: &T, new: &T) -> SaveResult<Vec<Change>>
+where
+    T: SaveBin + Model,
+    SaveError: From<T::WriteError>,
+{
+    let mut old_bytes = vec![0; T::size()];
+    let mut new_bytes = vec![0; T::size()];
+    old.write(&mut old_bytes)?;
+    new.write(&mut new_bytes)?;
+    Ok(diff_bytes::<T>(&old_bytes, &new_bytes))
+}
+
+/// Compares two save or system files in their binary format, and returns the values
+/// that changed, sorted by offset.
+///
+/// Unlike [`diff`], this also reports changes to bytes that aren't part of any known field.
+///
+/// ## Panics
+/// Panics if either slice is smaller than the binary size of `T`.
+pub fn diff_bytes<T: SaveBin + Model>(old: &[u8], new: &[u8]) -> Vec<Change> {
+    let size = T::size();
+    let mut differ = Differ {
+        old: &old[..size],
+        new: &new[..size],
+        changes: Vec::new(),
+    };
+    differ.visit(&T::model(), "", 0, size);
+    differ.changes.sort_by_key(|c| c.offset);
+    differ.changes
+}
+
+impl<'a> Differ<'a> {
+    fn visit(&mut self, model: &TypeModel, path: &str, offset: usize, size: usize) {
+        let range = offset..offset + size;
+        if self.old[range.clone()] == self.new[range] {
+            return;
+        }
+        match model {
+            TypeModel::Empty => {}
+            TypeModel::Flags(model) => self.visit(&model.storage, path, offset, size),
+            // Enums are compared by discriminant
+            TypeModel::Primitive(_) | TypeModel::Enum(_) => {
+                let name = model.primitive_type().unwrap();
+                self.push(path.to_string(), offset, |bytes| {
+                    PrimitiveValue::read(name, &bytes[offset..offset + size])
+                        .unwrap_or_else(|_| panic!("unknown primitive type {name}"))
+                })
+            }
+            TypeModel::Array(array) => self.visit_items(&array.0, array.1, path, offset),
+            // Dynamic arrays and strings are compared up to their capacity
+            TypeModel::Vec(model) => {
+                self.visit_items(&model.item, model.capacity, path, offset);
+                let items_len = model.item.size() * model.capacity;
+                self.visit_len(model.len_type, path, offset + items_len, size - items_len);
+            }
+            TypeModel::String(model) => {
+                let byte = TypeModel::Primitive("u8");
+                self.visit_items(&byte, model.capacity, path, offset);
+                if let Some(len_type) = model.len_type {
+                    let len_size = size - model.capacity;
+                    self.visit_len(len_type, path, offset + model.capacity, len_size);
+                }
+            }
+            TypeModel::BitFlags(model) => self.visit_bit_flags(model, path, offset),
+            TypeModel::Struct(model) => match model.name {
+                // Flags are stored in a single array, skip the field name
+                "ByteFlags" => {
+                    let field = &model.fields[0];
+                    self.visit(&field.type_model, path, offset, field.size)
+                }
+                _ => self.visit_struct(model, path, offset, size),
+            },
+        }
+    }
+
+    fn visit_items(&mut self, item_model: &TypeModel, len: usize, path: &str, offset: usize) {
+        let item_size = item_model.size();
+        for i in 0..len {
+            self.visit(
+                item_model,
+                &format!("{path}[{i}]"),
+                offset + i * item_size,
+                item_size,
+            );
+        }
+    }
+
+    fn visit_len(&mut self, len_type: &'static str, path: &str, offset: usize, size: usize) {
+        let model = TypeModel::Primitive(len_type);
+        self.visit(&model, &format!("{path}.len"), offset, size);
+    }
+
+    fn visit_struct(&mut self, model: &StructModel, path: &str, offset: usize, size: usize) {
+        let mut known = vec![false; size];
+        for field in &model.fields {
+            known[field.offset..field.offset + field.size].fill(true);
+            let field_path = match path {
+                "" => field.name.to_string(),
+                _ => format!("{path}.{}", field.name),
+            };
+            self.visit(
+                &field.type_model,
+                &field_path,
+                offset + field.offset,
+                field.size,
+            );
+        }
+
+        let unknown = known.iter().enumerate().filter(|(_, &k)| !k);
+        for (rel, _) in unknown {
+            let abs = offset + rel;
+            if self.old[abs] != self.new[abs] {
+                self.push(format!("{path}@{rel:#x}"), abs, |bytes| {
+                    PrimitiveValue::U8(bytes[abs])
+                });
+            }
+        }
+    }
+
+    /// Reports changes to individual flags in a packed flag array.
+    fn visit_bit_flags(&mut self, model: &BitFlagsModel, path: &str, offset: usize) {
+        let bits = model.bits;
+        let per_word = u32::BITS as usize / bits;
+        let mask = u32::MAX >> (u32::BITS as usize - bits);
+
+        for word in 0..model.words {
+            let word_offset = offset + word * 4;
+            let old = LittleEndian::read_u32(&self.old[word_offset..]);
+            let new = LittleEndian::read_u32(&self.new[word_offset..]);
+            if old == new {
+                continue;
+            }
+            for i in 0..per_word {
+                let shift = i * bits;
+                let (old, new) = ((old >> shift) & mask, (new >> shift) & mask);
+                if old != new {
+                    self.changes.push(Change {
+                        path: format!("{path}[{}]", word * per_word + i),
+                        offset: word_offset,
+                        old: PrimitiveValue::U32(old),
+                        new: PrimitiveValue::U32(new),
+                    });
+                }
+            }
+        }
+    }
+
+    fn push(&mut self, path: String, offset: usize, read: impl Fn(&[u8]) -> PrimitiveValue) {
+        self.changes.push(Change {
+            path,
+            offset,
+            old: read(self.old),
+            new: read(self.new),
+        });
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (0x{:x}): {} -> {}",
+            self.path, self.offset, self.old, self.new
+        )
+    }
+}
@@ -1,6 +1,9 @@
+use recordkeeper_data_model::DynamicError;
 use std::convert::Infallible;
 use thiserror::Error;
 
+use crate::check::Problem;
+
 #[derive(Error, Debug)]
 pub enum SaveError {
     #[error("Data file is unknown or unsupported.")]
@@ -16,8 +19,175 @@ pub enum SaveError {
     Io(#[from] std::io::Error),
     #[error("unreachable")]
     Infallible(#[from] Infallible),
-    #[error("Unexpected EOF while writing byte")]
+    #[error("Unexpected EOF while writing data")]
     UnexpectedEof,
     #[error("Could not set crafted accessory data: the craft inventory is full.")]
     MashaInventoryFull,
+    #[error("Unknown item ID {0}")]
+    UnknownItem(u16),
+    #[error("Could not add item {item_id}: invalid amount {amount}.")]
+    InvalidItemAmount { item_id: u16, amount: u16 },
+    #[error("Could not add item {0}: there are no free slots for its type.")]
+    InventoryFull(u16),
+    #[error("Character {0} is not selectable, it must be unlocked first.")]
+    UnselectableCharacter(u16),
+    #[error("Character {0} is already in the party.")]
+    AlreadyInParty(u16),
+    #[error("Character {0} is not in the party.")]
+    NotInParty(u16),
+    #[error("Could not add {0} to the party: the party is full.")]
+    PartyFull(u16),
+    #[error("Could not remove {0} from the party: it is the last member.")]
+    LastPartyMember(u16),
+    #[error("The party formation slot is empty.")]
+    EmptyFormation,
+    #[error(
+        "Could not apply formation: accessory {accessory} of character {character_id}: {problem}"
+    )]
+    FormationAccessory {
+        character_id: u16,
+        accessory: usize,
+        problem: Problem,
+    },
+    #[error("Could not apply formation: invalid character ID {0}.")]
+    InvalidFormationCharacter(u16),
+    #[error(
+        "Could not apply formation: invalid class ID {class_id} for character {character_id}."
+    )]
+    InvalidFormationClass { character_id: u16, class_id: u16 },
+    #[error("Could not apply formation: invalid Ouroboros ID {0}.")]
+    InvalidFormationOuroboros(u16),
+    #[error(transparent)]
+    Model(#[from] DynamicError),
+    #[error(transparent)]
+    Discriminant(#[from] DiscriminantError),
+    #[error("Invalid field {path} ({struct_name}.{field}) at offset {offset:#x}: {source}")]
+    Field {
+        /// Name of the struct that contains the field. This is empty if the error
+        /// occurred in an array that is not part of any struct.
+        struct_name: &'static str,
+        field: &'static str,
+        /// Full path to the field, e.g. `characters[3].level`
+        path: String,
+        /// Offset of the field from the start of the file
+        offset: u64,
+        source: Box<SaveError>,
+    },
+}
+
+/// Error returned when converting an integer to an enum, if no variant has that
+/// discriminant.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Unknown {enum_name} value {value}")]
+pub struct DiscriminantError {
+    pub enum_name: &'static str,
+    pub value: u64,
+}
+
+impl SaveError {
+    /// Returns the underlying error, skipping field location information.
+    pub fn inner(&self) -> &SaveError {
+        match self {
+            SaveError::Field { source, .. } => source.inner(),
+            e => e,
+        }
+    }
+
+    /// Adds the location of a struct field to a read error. `offset` is the absolute
+    /// offset of the field.
+    ///
+    /// If the error already has a location, the field is prepended to its path.
+    ///
+    /// Used by derived [`SaveBin`](crate::io::SaveBin) implementations.
+    #[doc(hidden)]
+    pub fn in_field(self, struct_name: &'static str, field: &'static str, offset: u64) -> Self {
+        self.locate(field.to_string(), Some((struct_name, field)), offset, false)
+    }
+
+    /// Adds an array index to the location of a read error. `pos` is the offset of the
+    /// item relative to the start of the array.
+    pub(crate) fn in_index(self, index: usize, pos: usize) -> Self {
+        self.locate(format!("[{index}]"), None, pos as u64, false)
+    }
+
+    /// Adds the location of a struct field to a write error. `pos` is the offset of the
+    /// field relative to the buffer being written, which is the struct itself.
+    ///
+    /// If the error already has a location, its offset is moved forward by `pos`.
+    ///
+    /// Used by derived [`SaveBin`](crate::io::SaveBin) implementations.
+    #[doc(hidden)]
+    pub fn in_written_field(
+        self,
+        struct_name: &'static str,
+        field: &'static str,
+        pos: usize,
+    ) -> Self {
+        self.locate(
+            field.to_string(),
+            Some((struct_name, field)),
+            pos as u64,
+            true,
+        )
+    }
+
+    /// Adds an array index to the location of a write error. `pos` is the offset of the
+    /// item relative to the start of the array.
+    pub(crate) fn in_written_index(self, index: usize, pos: usize) -> Self {
+        self.locate(format!("[{index}]"), None, pos as u64, true)
+    }
+
+    /// Prepends a field name or array index to the path of an error, adding a location
+    /// if the error doesn't have one yet.
+    ///
+    /// `owner` is the struct and field name, for field segments. Array items only know their
+    /// position inside the array, so their location stays pending (with no struct name) until
+    /// the array is found in a struct field. Offsets of pending locations, and all write
+    /// offsets, are relative, so `pos` is added to them.
+    fn locate(
+        self,
+        segment: String,
+        owner: Option<(&'static str, &'static str)>,
+        pos: u64,
+        relative: bool,
+    ) -> Self {
+        match self {
+            SaveError::Field {
+                struct_name,
+                field,
+                path,
+                offset,
+                source,
+            } => {
+                let pending = struct_name.is_empty();
+                let (struct_name, field) = match owner {
+                    Some(owner) if pending => owner,
+                    _ => (struct_name, field),
+                };
+                SaveError::Field {
+                    struct_name,
+                    field,
+                    path: match path.starts_with('[') {
+                        true => format!("{segment}{path}"),
+                        false => format!("{segment}.{path}"),
+                    },
+                    offset: match relative || pending {
+                        true => offset + pos,
+                        false => offset,
+                    },
+                    source,
+                }
+            }
+            e => {
+                let (struct_name, field) = owner.unwrap_or(("", ""));
+                SaveError::Field {
+                    struct_name,
+                    field,
+                    path: segment,
+                    offset: pos,
+                    source: Box::new(e),
+                }
+            }
+        }
+    }
 }
@@ -4,6 +4,9 @@ use std::convert::Infallible;
 use std::io::Cursor;
 use std::marker::PhantomData;
 
+#[cfg(feature = "serde")]
+pub mod serde;
+
 /// Provides support for reading structs from save files, and writing to portions of them.
 ///
 /// ## Derive macro
@@ -16,8 +19,8 @@ use std::marker::PhantomData;
 ///
 /// #[derive(SaveBin)]
 /// // We can provide a size hint manually. If the actual size is bigger,
-/// // reads will panic. If it is smaller, extra space will be accounted
-/// // for when reading or writing.
+/// // the struct will fail to compile. If it is smaller, extra space will
+/// // be accounted for when reading or writing.
 /// #[size(32)]
 /// struct Position {
 ///     // fields must be of types that implement SaveBin
@@ -27,7 +30,8 @@ use std::marker::PhantomData;
 ///
 ///     // We don't know what the other fields are, but
 ///     // we can skip them by forcing an offset. The
-///     // offset is relative to the start of the struct.
+///     // offset is relative to the start of the struct,
+///     // and must not overlap the previous field.
 ///     #[loc(0x10)]
 ///     yaw: f32,
 ///     pitch: f32,
@@ -61,7 +65,16 @@ use std::marker::PhantomData;
 /// #[assert(1, CustomErrorWithValue(ACTUAL))]
 /// ```
 ///
+/// Errors from derived reads, including failed assertions, are wrapped in a
+/// [`SaveError::Field`], which records the path and offset of the field that could not be read.
+///
+/// ## Layout checks
+/// The struct layout is checked at compile time: fields may not be placed before the end of
+/// the previous field, and fields may not extend past the size given with `size`. Both
+/// cases fail to compile (see `tests/ui` for examples).
+///
 /// [`AssertionError`]: crate::error::SaveError::AssertionError
+/// [`SaveError::Field`]: crate::error::SaveError::Field
 pub trait SaveBin: Sized {
     type ReadError;
     type WriteError;
@@ -74,14 +87,28 @@ pub trait SaveBin: Sized {
 
     /// Writes the type to a byte buffer.
     ///
-    /// ## Panics
-    /// This function may panic if there isn't enough space to write the data.
-    /// When writing save files, the old save file data should be used as the base.
+    /// ## Errors
+    /// If there isn't enough space to write the data, [`SaveError::UnexpectedEof`] is
+    /// returned, and the buffer may be left partially written.
+    ///
+    /// When writing save files, the old save file data should be used as the base,
+    /// or a zeroed buffer of [`size`] bytes (see [`FileBuffer::to_bytes`]).
+    ///
+    /// [`size`]: SaveBin::size
+    /// [`FileBuffer::to_bytes`]: crate::FileBuffer::to_bytes
     fn write(&self, bytes: &mut [u8]) -> Result<(), Self::WriteError>;
 
+    /// The total size of this type *when serialized into the save binary format*.
+    ///
+    /// For derived implementations, this is computed at compile time, along with
+    /// layout checks for the `loc` and `size` attributes.
+    const SIZE: usize = std::mem::size_of::<Self>();
+
     /// Returns the total size of this type *when serialized into the save binary format*.
+    ///
+    /// This is equal to [`SIZE`](SaveBin::SIZE).
     fn size() -> usize {
-        std::mem::size_of::<Self>()
+        Self::SIZE
     }
 }
 
@@ -90,13 +117,16 @@ macro_rules! byteorder_impl {
         $(
             impl SaveBin for $types {
                 type ReadError = std::io::Error;
-                type WriteError = std::convert::Infallible;
+                type WriteError = SaveError;
 
                 fn read(bytes: &mut Cursor<&[u8]>) -> Result<Self, Self::ReadError> {
                     paste::paste! { bytes.[<read_ $types >]::<LittleEndian>() }
                 }
 
                 fn write(&self, bytes: &mut [u8]) -> Result<(), Self::WriteError> {
+                    let bytes = bytes
+                        .get_mut(..std::mem::size_of::<Self>())
+                        .ok_or(SaveError::UnexpectedEof)?;
                     paste::paste! { LittleEndian::[<write_ $types >](bytes, *self) }
                     Ok(())
                 }
@@ -166,14 +196,15 @@ impl<T, const N: usize> SaveBin for [T; N]
 where
     T: SaveBin,
     T::ReadError: Into<SaveError>,
+    SaveError: From<T::WriteError>,
 {
     type ReadError = SaveError;
-    type WriteError = T::WriteError;
+    type WriteError = SaveError;
 
     fn read(bytes: &mut Cursor<&[u8]>) -> Result<Self, Self::ReadError> {
         let mut items = Vec::with_capacity(N);
-        for _ in 0..N {
-            let item = T::read(bytes).map_err(Into::into)?;
+        for i in 0..N {
+            let item = T::read(bytes).map_err(|e| e.into().in_index(i, i * T::SIZE))?;
             items.push(item);
         }
         // Unreachable since we return early if we do not successfully read N elements.
@@ -187,18 +218,17 @@ where
         let mut pos = 0;
         let item_size = T::size();
 
-        for item in self {
-            let cur_bytes = &mut bytes[pos..];
-            item.write(cur_bytes)?;
+        for (i, item) in self.iter().enumerate() {
+            let cur_bytes = bytes.get_mut(pos..).ok_or(SaveError::UnexpectedEof)?;
+            item.write(cur_bytes)
+                .map_err(|e| SaveError::from(e).in_written_index(i, pos))?;
             pos += item_size;
         }
 
         Ok(())
     }
 
-    fn size() -> usize {
-        T::size() * N
-    }
+    const SIZE: usize = T::SIZE * N;
 }
 
 impl<T: SaveBin, const N: usize> SaveBin for Box<[T; N]>
@@ -206,15 +236,15 @@ where
     SaveError: From<<T as SaveBin>::ReadError>,
     SaveError: From<<T as SaveBin>::WriteError>,
 {
-    type ReadError = T::ReadError;
+    type ReadError = SaveError;
 
-    type WriteError = T::WriteError;
+    type WriteError = SaveError;
 
     fn read(bytes: &mut Cursor<&[u8]>) -> Result<Self, Self::ReadError> {
         // Read into a vec first to avoid large stack allocations with Box::new.
         let mut items = Vec::with_capacity(N);
-        for _ in 0..N {
-            let item = T::read(bytes)?;
+        for i in 0..N {
+            let item = T::read(bytes).map_err(|e| SaveError::from(e).in_index(i, i * T::SIZE))?;
             items.push(item);
         }
         // Unreachable since we return early if we do not successfully read N elements.
@@ -229,7 +259,5 @@ where
         values.write(bytes)
     }
 
-    fn size() -> usize {
-        T::size() * N
-    }
+    const SIZE: usize = T::SIZE * N;
 }
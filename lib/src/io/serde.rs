@@ -0,0 +1,199 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use ::serde::de::{DeserializeSeed, Error, SeqAccess, Visitor};
+use ::serde::ser::SerializeTuple;
+use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Serde support for types that can be read from save files.
+///
+/// Serde only implements its traits for arrays of up to 32 elements, which is not enough
+/// for most save file tables. This trait is implemented for arrays of any size, and for
+/// every type that derives `SaveBin`, which also get regular [`Serialize`] and
+/// [`Deserialize`] implementations.
+///
+/// Every field is included, even private ones, so deserialized data can be written back
+/// without losing information, e.g.
+/// ```ignore
+/// let data: SaveData = serde_json::from_str(&json)?;
+/// *save_file.save_mut() = data;
+/// save_file.write()?;
+/// ```
+///
+/// Values checked with `#[assert]` when reading, like the save file version, are also checked
+/// when deserializing.
+///
+/// Like [`SaveBin::read`], deserializing creates the structure on the stack. In debug builds,
+/// deserializing [`SaveData`] needs a few megabytes of stack, more than the 2 MiB that spawned
+/// threads get by default, so it should run on a thread with a larger stack, e.g.
+/// ```ignore
+/// let data: SaveData = std::thread::Builder::new()
+///     .stack_size(8 * 1024 * 1024)
+///     .spawn(move || serde_json::from_str(&json))?
+///     .join()
+///     .unwrap()?;
+/// ```
+/// Release builds fit in the default stack size.
+///
+/// [`SaveBin::read`]: crate::io::SaveBin::read
+/// [`SaveData`]: crate::SaveData
+pub trait SerdeBin: Sized {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+
+    fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>;
+}
+
+/// Wrapper that implements [`Serialize`] for any [`SerdeBin`] type.
+#[doc(hidden)]
+pub struct Ser<'a, T>(pub &'a T);
+
+/// Deserialization seed for any [`SerdeBin`] type.
+#[doc(hidden)]
+pub struct Seed<T>(PhantomData<T>);
+
+struct ArrayVisitor<T, const N: usize>(PhantomData<T>);
+
+macro_rules! serde_impl {
+    ($($types:tt ) *) => {
+        $(
+            impl SerdeBin for $types {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    Serialize::serialize(self, serializer)
+                }
+
+                fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    Deserialize::deserialize(deserializer)
+                }
+            }
+        )*
+    };
+}
+
+serde_impl!(bool u8 i8 u64 i64 f64 u32 i32 f32 u16 i16);
+
+impl<T> SerdeBin for PhantomData<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Serialize::serialize(self, serializer)
+    }
+
+    fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Deserialize::deserialize(deserializer)
+    }
+}
+
+impl<T: SerdeBin, const N: usize> SerdeBin for [T; N] {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(N)?;
+        for item in self {
+            tuple.serialize_element(&Ser(item))?;
+        }
+        tuple.end()
+    }
+
+    fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let items = deserializer.deserialize_tuple(N, ArrayVisitor::<T, N>(PhantomData))?;
+        // Unreachable since the visitor checks the length.
+        match items.try_into() {
+            Ok(items) => Ok(items),
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
+impl<T: SerdeBin, const N: usize> SerdeBin for Box<[T; N]> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let values: &[T; N] = self;
+        values.serialize(serializer)
+    }
+
+    fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Read into a vec first to avoid large stack allocations with Box::new.
+        let items = deserializer.deserialize_tuple(N, ArrayVisitor::<T, N>(PhantomData))?;
+        // Unreachable since the visitor checks the length.
+        match items.into_boxed_slice().try_into() {
+            Ok(items) => Ok(items),
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
+impl<'a, T: SerdeBin> Serialize for Ser<'a, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<T> Seed<T> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Default for Seed<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'de, T: SerdeBin> DeserializeSeed<'de> for Seed<T> {
+    type Value = T;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        T::deserialize(deserializer)
+    }
+}
+
+impl<'de, T: SerdeBin, const N: usize> Visitor<'de> for ArrayVisitor<T, N> {
+    type Value = Vec<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "an array of length {N}")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut items = Vec::with_capacity(N);
+        while let Some(item) = seq.next_element_seed(Seed::new())? {
+            if items.len() == N {
+                return Err(A::Error::invalid_length(N + 1, &self));
+            }
+            items.push(item);
+        }
+        if items.len() != N {
+            return Err(A::Error::invalid_length(items.len(), &self));
+        }
+        Ok(items)
+    }
+}
+
+/// Deserialization seed for struct field names. Returns the index of the field in the given
+/// list, or [`None`] if the field is unknown.
+#[doc(hidden)]
+pub struct FieldSeed(pub &'static [&'static str]);
+
+impl<'de> DeserializeSeed<'de> for FieldSeed {
+    type Value = Option<usize>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl<'de> Visitor<'de> for FieldSeed {
+    type Value = Option<usize>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a field identifier")
+    }
+
+    fn visit_u64<E: Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(usize::try_from(v).ok().filter(|&i| i < self.0.len()))
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(self.0.iter().position(|&f| f == v))
+    }
+
+    fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(self.0.iter().position(|&f| f.as_bytes() == v))
+    }
+}
@@ -1,4 +1,8 @@
-mod error;
+// Derived code refers to this crate by name, so it also works in other crates
+extern crate self as recordkeeper;
+
+pub mod diff;
+pub mod error;
 pub mod io;
 mod save;
 mod system;
@@ -7,6 +11,7 @@ pub mod util;
 use std::io::Cursor;
 
 use crate::error::SaveError;
+use recordkeeper_data_model::{DynamicError, DynamicValue, Model, PrimitiveValue, TypeModel};
 
 use crate::io::SaveBin;
 pub use save::*;
@@ -27,6 +32,19 @@ pub enum DataFile {
     System(SystemFile),
 }
 
+/// Type and format version of a data file, read from its header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileHeader {
+    pub kind: FileKind,
+    pub version: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    Save,
+    System,
+}
+
 impl DataFile {
     /// Reads a save or system file from a slice, and allocates it on the heap.
     /// Both the given buffer and the parsed save file will be allocated.
@@ -87,6 +105,40 @@ impl DataFile {
     }
 }
 
+impl FileHeader {
+    /// Reads the header of a save or system file, without parsing the rest of the file.
+    ///
+    /// Unlike [`DataFile::from_bytes`], this also succeeds for format versions that are
+    /// not supported by this library, so files from older game versions can still be
+    /// identified.
+    pub fn read(bytes: &[u8]) -> SaveResult<Self> {
+        if bytes.len() < 8 {
+            return Err(SaveError::UnexpectedEof);
+        }
+        let magic: [u8; 4] = bytes[0..4].try_into().unwrap();
+        let (kind, version) = match magic {
+            SAVE_MAGIC => (FileKind::Save, bytes[4].into()),
+            SYSTEM_MAGIC => (
+                FileKind::System,
+                u32::from_le_bytes(bytes[4..8].try_into().unwrap()),
+            ),
+            _ => return Err(SaveError::UnrecognizedFormat),
+        };
+        Ok(Self { kind, version })
+    }
+
+    /// Returns whether files with this header can be parsed by this library.
+    ///
+    /// Only the latest format version of each file type is supported, as the layouts
+    /// used by older game versions are not documented.
+    pub fn is_supported(&self) -> bool {
+        match self.kind {
+            FileKind::Save => self.version == u32::from(SAVE_VERSION),
+            FileKind::System => self.version == SYSTEM_VERSION,
+        }
+    }
+}
+
 impl<T> FileBuffer<T> {
     pub fn bytes(&self) -> &[u8] {
         self.bytes.as_ref()
@@ -101,7 +153,121 @@ impl<T> FileBuffer<T> {
     }
 }
 
+impl<T> FileBuffer<T>
+where
+    T: SaveBin + Model,
+    SaveError: From<T::ReadError> + From<T::WriteError>,
+{
+    /// Reads the primitive value at the given path, e.g. `characters[0].level`.
+    ///
+    /// Paths use the field names from the data model, so fields that aren't public
+    /// can also be read. See [`TypeModel::find_path`] for the path syntax. Enum fields
+    /// are read as their discriminant. Indices into dynamic arrays (e.g. the party) must
+    /// be lower than the array length.
+    ///
+    /// The value is read from the file buffer, so changes to the parsed data are only
+    /// visible after they are written to the buffer.
+    pub fn get_path(&self, path: &str) -> SaveResult<PrimitiveValue> {
+        let model = T::model();
+        let (offset, field) = model.find_path_in(path, &self.bytes)?;
+        let Some(type_name) = field.primitive_type() else {
+            return Err(not_primitive(field));
+        };
+        Ok(PrimitiveValue::read(type_name, &self.bytes[offset..])?)
+    }
+
+    /// Sets the primitive value at the given path, e.g. `characters[0].level`.
+    ///
+    /// The value must have the same type as the field, and indices into dynamic arrays
+    /// must be lower than the array length. After the change, the file is parsed again,
+    /// so pending changes are also written to the file buffer.
+    ///
+    /// ## Errors
+    /// If the file could not be parsed with the new value, the change is reverted and the
+    /// parse error is returned.
+    pub fn set_path(&mut self, path: &str, value: PrimitiveValue) -> SaveResult<()> {
+        self.parsed.write(&mut self.bytes)?;
+        let model = T::model();
+        let (offset, field) = model.find_path_in(path, &self.bytes)?;
+        if field.primitive_type().is_none() {
+            return Err(not_primitive(field));
+        }
+
+        let range = offset..offset + field.size();
+        let old = self.bytes[range.clone()].to_vec();
+        DynamicValue::Primitive(value).encode(field, &mut self.bytes[range.clone()])?;
+        match T::read(&mut Cursor::new(&self.bytes)) {
+            Ok(parsed) => {
+                *self.parsed = parsed;
+                Ok(())
+            }
+            Err(e) => {
+                self.bytes[range].copy_from_slice(&old);
+                Err(e.into())
+            }
+        }
+    }
+}
+
+impl<T> FileBuffer<T>
+where
+    T: SaveBin,
+    SaveError: From<T::WriteError>,
+{
+    /// Serializes the parsed data into a new buffer, instead of using the original
+    /// file as a base.
+    ///
+    /// Bytes that are not part of any known field are set to zero, so any data that
+    /// is not supported by this library is lost.
+    pub fn to_bytes(&self) -> SaveResult<Box<[u8]>> {
+        let mut bytes = vec![0; T::size()].into_boxed_slice();
+        self.parsed.write(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+impl<T> FileBuffer<T>
+where
+    T: SaveBin + Model,
+    SaveError: From<T::ReadError>,
+{
+    /// Creates a zeroed file, with the given fields set, then parses it.
+    fn from_fields(fields: &[(&str, DynamicValue)]) -> SaveResult<Self> {
+        let model = T::model();
+        let mut bytes = vec![0; T::size()].into_boxed_slice();
+        for (path, value) in fields {
+            let (offset, field) = model.find_path(path)?;
+            value.encode(field, &mut bytes[offset..])?;
+        }
+        let parsed = T::read(&mut Cursor::new(&bytes))?;
+
+        Ok(Self {
+            parsed: Box::new(parsed),
+            bytes,
+        })
+    }
+}
+
+fn not_primitive(model: &TypeModel) -> SaveError {
+    DynamicError::TypeMismatch {
+        expected: "primitive".to_string(),
+        found: model.to_string(),
+    }
+    .into()
+}
+
 impl SaveFile {
+    /// Creates a save file where every field has its zero value, except for the
+    /// header and the few fields needed for it to be parsed by this library.
+    ///
+    /// This is only meant as a fixture for tests and tools that work on parsed data.
+    /// The game can't load the result: there is no party, no controlled character,
+    /// and no valid location, and this library doesn't know the values a new game
+    /// starts with.
+    pub fn zeroed() -> Self {
+        Self::from_fields(&SaveData::required_fields()).expect("minimal save should be valid")
+    }
+
     /// Reads a save file from a slice, and allocates it on the heap.
     ///
     /// Both the given buffer and the parsed save file will be allocated.
@@ -121,6 +287,12 @@ impl SaveFile {
 }
 
 impl SystemFile {
+    /// Creates a new system file, where every field has its zero value.
+    pub fn new() -> Self {
+        Self::from_fields(&SystemData::required_fields())
+            .expect("minimal system file should be valid")
+    }
+
     /// Reads a system file from a slice, and allocates it on the heap.
     ///
     /// Both the given buffer and the parsed save file will be allocated.
@@ -138,3 +310,9 @@ impl SystemFile {
         self.parsed.write(self.bytes.as_mut())
     }
 }
+
+impl Default for SystemFile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
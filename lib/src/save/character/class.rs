@@ -1,6 +1,13 @@
-use recordkeeper_macros::SaveBin;
+use std::num::NonZeroU32;
 
-use crate::item::{ItemSlot, ItemType};
+use recordkeeper_macros::{Flag, SaveBin};
+
+use crate::{
+    chrono::{CharacterChronoAccessor, ChronologicalOrder},
+    flags::Flags,
+    item::{ItemSlot, ItemType},
+    SaveData,
+};
 
 use super::slot::{EmptySlot, EmptySlotMut, Slot, SlotMut};
 
@@ -15,7 +22,7 @@ pub struct CharacterClass {
     pub cp: u32,
     pub unlock_points: u16,
     pub level: u8,
-    flags: u8,
+    pub flags: Flags<ClassFlags, u8>,
 
     /// The raw value is `ITM_Gem.Category - 1`.
     /// For gem levels, see [`SaveData::gem_levels`](crate::SaveData::gem_levels)
@@ -27,6 +34,19 @@ pub struct CharacterClass {
     accessories: [ClassAccessory; CHARACTER_CLASS_ACCESSORY_MAX],
 }
 
+/// Unlocks classes and edits their rank, keeping the class unlock order in sync.
+///
+/// The unlock order ([`ChronologicalData::character_classes`]) is only tracked for
+/// the first 6 characters in base game saves, and for Matthew in DLC4 (Future Redeemed)
+/// saves. For other characters, only the class data is updated.
+///
+/// [`ChronologicalData::character_classes`]: crate::chrono::ChronologicalData::character_classes
+pub struct ClassEditor<'a> {
+    class: &'a mut CharacterClass,
+    order: Option<CharacterChronoAccessor<'a, 64, 6>>,
+    class_index: usize,
+}
+
 /// Accessory slot data.
 ///
 /// What is important here is `slot_index`, changing the BDAT ID
@@ -38,6 +58,7 @@ pub struct ClassAccessory {
     item_type: u16,
 }
 
+#[derive(Flag, Clone, Copy, Debug, PartialEq)]
 pub enum ClassFlags {
     /// Level cap unlocked
     Ascended = 0,
@@ -110,13 +131,134 @@ impl CharacterClass {
     pub fn accessories_mut(&mut self) -> impl Iterator<Item = SlotMut<ClassAccessory>> + '_ {
         self.accessories.iter_mut().map(SlotMut)
     }
+
+    pub(crate) fn remap_accessories(&mut self, item_type: ItemType, slot_map: &[Option<u16>]) {
+        for accessory in &mut self.accessories {
+            accessory.remap_slot(item_type, slot_map);
+        }
+    }
+}
+
+impl<'a> ClassEditor<'a> {
+    /// Creates a class editor for the given character and class.
+    ///
+    /// ## Panics
+    /// Panics if either ID is out of bounds.
+    pub fn new(save: &'a mut SaveData, character_id: NonZeroU32, class_id: NonZeroU32) -> Self {
+        let char_index = usize::try_from(character_id.get() - 1).unwrap();
+        let class_index = usize::try_from(class_id.get() - 1).unwrap();
+        let column = class_order_column(character_id, save.is_dlc4());
+        let class = save.characters[char_index].class_data_mut(class_id);
+        let order = column.map(|column| save.chronological_data.character_class_order(column));
+        Self {
+            class,
+            order,
+            class_index,
+        }
+    }
+
+    pub fn class(&self) -> &CharacterClass {
+        self.class
+    }
+
+    /// Returns whether the class is unlocked, i.e. its rank is at least 1.
+    pub fn is_unlocked(&self) -> bool {
+        self.class.level > 0
+    }
+
+    /// Unlocks the class at rank 1. This does nothing if the class is already unlocked.
+    ///
+    /// If the class was not in the character's class list, it is added as the most
+    /// recently unlocked class.
+    pub fn unlock(&mut self) {
+        if self.class.level == 0 {
+            self.class.level = 1;
+        }
+        if let Some(order) = &mut self.order {
+            if !order.is_present(self.class_index) {
+                order.insert(self.class_index);
+            }
+        }
+    }
+
+    /// Locks the class, resetting its rank, CP and level cap.
+    ///
+    /// The class stays in the character's class list, like classes that have been
+    /// discovered but not yet unlocked. Unlock points are kept.
+    pub fn lock(&mut self) {
+        self.class.level = 0;
+        self.class.cp = 0;
+        self.class.flags.set(ClassFlags::Ascended, false);
+    }
+
+    pub fn rank(&self) -> u8 {
+        self.class.level
+    }
+
+    /// Sets the class rank, unlocking the class if needed.
+    ///
+    /// A rank of `0` locks the class, see [`ClassEditor::lock`].
+    pub fn set_rank(&mut self, rank: u8) {
+        if rank == 0 {
+            self.lock();
+            return;
+        }
+        self.unlock();
+        self.class.level = rank;
+    }
+
+    pub fn set_cp(&mut self, cp: u32) {
+        self.class.cp = cp;
+    }
+
+    pub fn set_unlock_points(&mut self, unlock_points: u16) {
+        self.class.unlock_points = unlock_points;
+    }
+
+    /// Returns whether the class level cap is unlocked.
+    pub fn is_ascended(&self) -> bool {
+        self.class.flags.is_set(ClassFlags::Ascended)
+    }
+
+    /// Unlocks or locks the class level cap.
+    pub fn set_ascended(&mut self, ascended: bool) {
+        self.class.flags.set(ClassFlags::Ascended, ascended);
+    }
+}
+
+/// Returns the column of [`ChronologicalData::character_classes`] for a character,
+/// if its class unlock order is tracked.
+///
+/// [`ChronologicalData::character_classes`]: crate::chrono::ChronologicalData::character_classes
+fn class_order_column(character_id: NonZeroU32, is_dlc4: bool) -> Option<usize> {
+    // Only Matthew's column is known for DLC4 saves
+    const MATTHEW_ID: u32 = 36;
+    match (is_dlc4, character_id.get()) {
+        (false, id @ 1..=6) => Some(usize::try_from(id - 1).unwrap()),
+        (true, MATTHEW_ID) => Some(0),
+        _ => None,
+    }
 }
 
 impl ClassAccessory {
+    pub(crate) fn new_raw(bdat_id: u16, slot_index: u16, item_type: u16) -> Self {
+        Self {
+            bdat_id,
+            slot_index,
+            item_type,
+        }
+    }
+
     pub fn bdat_id(&self) -> u16 {
         self.bdat_id
     }
 
+    /// Returns the item type without converting it, as heroes' default accessories
+    /// use types that are not part of [`ItemType`].
+    pub(crate) fn raw_item_type(&self) -> u16 {
+        self.item_type
+    }
+
     pub fn item_type(&self) -> ItemType {
         ItemType::try_from(u32::from(self.item_type)).expect("invalid item type")
     }
@@ -124,6 +266,34 @@ impl ClassAccessory {
     pub fn slot_index(&self) -> u16 {
         self.slot_index
     }
+
+    /// Returns whether the accessory slot refers to a slot in [`Inventory::accessories`].
+    ///
+    /// Heroes' default accessories use a different item type, and do not point
+    /// to the inventory.
+    ///
+    /// [`Inventory::accessories`]: crate::item::Inventory::accessories
+    pub(crate) fn is_inventory_accessory(&self) -> bool {
+        !Slot(*self).is_empty() && self.item_type == ItemType::Accessory as u16
+    }
+
+    /// Updates the inventory slot reference after the inventory for `item_type` has
+    /// been rearranged. `slot_map` maps old slot indices to new ones.
+    ///
+    /// If the old slot has no new position, the accessory slot is emptied.
+    pub(crate) fn remap_slot(&mut self, item_type: ItemType, slot_map: &[Option<u16>]) {
+        if Slot(*self).is_empty() || self.item_type != item_type as u16 {
+            return;
+        }
+        match slot_map
+            .get(usize::from(self.slot_index))
+            .copied()
+            .flatten()
+        {
+            Some(new_index) => self.slot_index = new_index,
+            None => *self = Self::default(),
+        }
+    }
 }
 
 impl<'a> SlotMut<'a, ClassAccessory> {
@@ -1,12 +1,14 @@
 use recordkeeper_macros::SaveBin;
 
-use crate::{util::FixVec, SaveData};
+use std::num::NonZeroU32;
+
+use crate::{check::check_class, error::SaveError, util::FixVec, SaveData, SaveResult};
 
 use super::{
     class::CharacterClass,
     slot::{Slot, SlotMut},
-    Character, Ouroboros, CHARACTER_MAX, OUROBOROS_ART_MAX, OUROBOROS_MAX, OUROBOROS_SKILL_MAX,
-    PARTY_MAX,
+    Character, Ouroboros, CHARACTER_CLASS_MAX, CHARACTER_MAX, OUROBOROS_ART_MAX, OUROBOROS_MAX,
+    OUROBOROS_SKILL_MAX, PARTY_MAX,
 };
 
 pub const PARTY_FORMATION_MAX: usize = 15;
@@ -29,10 +31,11 @@ pub struct FormationName {
     /// Discriminator, can be set in-game
     pub number: u16,
     /// Color ID, 0-14
+    #[range(0, 14)]
     pub color_id: u16,
 }
 
-#[derive(SaveBin, Debug)]
+#[derive(SaveBin, Debug, Default)]
 #[size(144)]
 pub struct CharacterFormation {
     #[loc(0x4)]
@@ -43,7 +46,7 @@ pub struct CharacterFormation {
     pub attachment: u8,
 }
 
-#[derive(SaveBin, Debug)]
+#[derive(SaveBin, Debug, Default)]
 pub struct OuroborosFormation {
     pub ouroboros_id: u16,
     pub art_ids: [u16; OUROBOROS_ART_MAX],
@@ -79,6 +82,97 @@ impl PartyFormation {
         }
     }
 
+    /// Restores the formation onto the save file.
+    ///
+    /// This sets the active party, and for each character in the formation, its
+    /// selected class, class loadout, costume and attachment. Ouroboros arts and
+    /// linked skills are also restored. If the controlled character is no longer
+    /// in the party, control is given to the party leader.
+    ///
+    /// ## Errors
+    /// Returns an error, without changing the save file, if:
+    /// * the formation is empty
+    /// * a party member is listed twice, or is not selectable in the save file (like
+    ///   with [`PartyEditor::add`])
+    /// * a character, class or Ouroboros ID is out of bounds
+    /// * one of its equipped accessories no longer matches the inventory
+    ///
+    /// [`PartyEditor::add`]: super::party::PartyEditor::add
+    pub fn apply_to(&self, save: &mut SaveData) -> SaveResult<()> {
+        self.validate(save)?;
+        let characters = self.characters.iter().filter(|c| c.character_id != 0);
+
+        let controlled = save
+            .party_characters
+            .get(usize::from(save.controlled_character_idx))
+            .copied();
+        save.party_characters = self.party.clone();
+        let controlled_idx = controlled.and_then(|id| self.party.iter().position(|&c| c == id));
+        save.controlled_character_idx = controlled_idx.unwrap_or(0).try_into().unwrap();
+
+        for formation in characters {
+            let character = &mut save.characters[usize::from(formation.character_id - 1)];
+            formation.apply_to_character(character);
+        }
+        for formation in self.ouroboros.iter().filter(|o| o.ouroboros_id != 0) {
+            let ouroboros = &mut save.ouroboros[usize::from(formation.ouroboros_id - 1)];
+            ouroboros.art_ids = formation.art_ids;
+            ouroboros.linked_skills = formation.linked_skills;
+        }
+        Ok(())
+    }
+
+    /// Checks that the formation can be applied to the save file.
+    fn validate(&self, save: &SaveData) -> SaveResult<()> {
+        if !self.is_valid() {
+            return Err(SaveError::EmptyFormation);
+        }
+        for (i, &id) in self.party.iter().enumerate() {
+            if self.party.iter().take(i).any(|&prev| prev == id) {
+                return Err(SaveError::AlreadyInParty(id));
+            }
+            if !save.character_sets.is_selectable(id) {
+                return Err(SaveError::UnselectableCharacter(id));
+            }
+        }
+
+        for formation in self.characters.iter().filter(|c| c.character_id != 0) {
+            let character_id = formation.character_id;
+            if usize::from(character_id) > CHARACTER_MAX {
+                return Err(SaveError::InvalidFormationCharacter(character_id));
+            }
+            if usize::from(formation.current_class) > CHARACTER_CLASS_MAX {
+                return Err(SaveError::InvalidFormationClass {
+                    character_id,
+                    class_id: formation.current_class,
+                });
+            }
+            if formation.current_class == 0 {
+                continue;
+            }
+            let mut error = None;
+            check_class(&save.inventory, &formation.class, |accessory, problem| {
+                error.get_or_insert(SaveError::FormationAccessory {
+                    character_id,
+                    accessory,
+                    problem: problem.into(),
+                });
+            });
+            if let Some(error) = error {
+                return Err(error);
+            }
+        }
+
+        match self
+            .ouroboros
+            .iter()
+            .find(|o| usize::from(o.ouroboros_id) > OUROBOROS_MAX)
+        {
+            Some(o) => Err(SaveError::InvalidFormationOuroboros(o.ouroboros_id)),
+            None => Ok(()),
+        }
+    }
+
     /// Returns whether a valid party formation is currently in the slot.
     pub fn is_valid(&self) -> bool {
         !self.party.is_empty()
@@ -148,6 +242,18 @@ impl PartyFormation {
     }
 }
 
+impl Default for PartyFormation {
+    /// Creates an empty party formation.
+    fn default() -> Self {
+        Self {
+            name: FormationName::default(),
+            party: FixVec::default(),
+            characters: std::array::from_fn(|_| CharacterFormation::default()),
+            ouroboros: std::array::from_fn(|_| OuroborosFormation::default()),
+        }
+    }
+}
+
 impl CharacterFormation {
     /// Creates a new character formation from the current state of the save file.
     pub fn from_save(save_char: &Character, char_id: u16) -> Self {
@@ -165,6 +271,16 @@ impl CharacterFormation {
         }
     }
 
+    /// Restores the selected class, class data, costume and attachment.
+    fn apply_to_character(&self, character: &mut Character) {
+        character.selected_class = self.current_class.try_into().unwrap();
+        if let Some(class_id) = NonZeroU32::new(self.current_class.into()) {
+            *character.class_data_mut(class_id) = self.class;
+        }
+        character.costume_id = self.costume_id;
+        character.attachment = self.attachment;
+    }
+
     /// Copies class data from the save file format of a character class.
     pub fn copy_class_from_save(&mut self, class: &CharacterClass) {
         self.class = *class;
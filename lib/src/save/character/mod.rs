@@ -1,8 +1,8 @@
 use std::num::NonZeroU32;
 
-use recordkeeper_macros::SaveBin;
+use recordkeeper_macros::{Flag, SaveBin};
 
-use crate::flags::BitFlags;
+use crate::flags::{BitFlags, Flags};
 
 use class::CharacterClass;
 use slot::{Slot, SlotMut};
@@ -12,13 +12,15 @@ pub const PARTY_GUEST_MAX: usize = 8;
 pub const CHARACTER_MAX: usize = 64;
 pub const OUROBOROS_MAX: usize = 6;
 
-const CHARACTER_CLASS_MAX: usize = 64;
+pub const CHARACTER_CLASS_MAX: usize = 64;
 
 pub const OUROBOROS_ART_MAX: usize = 5;
 pub const OUROBOROS_SKILL_MAX: usize = 2;
 
 pub mod class;
 pub mod formation;
+pub mod party;
+pub mod portable;
 pub mod slot;
 
 #[derive(SaveBin, Debug, Clone, Copy)]
@@ -28,7 +30,7 @@ pub struct Character {
     pub exp: u32,
     pub bonus_exp: u32,
 
-    flags: BitFlags<1, 1>,
+    pub flags: Flags<CharacterFlag, BitFlags<1, 1>>,
 
     #[loc(0x10)]
     pub selected_class: u8,
@@ -68,7 +70,7 @@ pub struct Ouroboros {
     pub skill_tree: OuroborosTree,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Flag, Clone, Copy, Debug, PartialEq)]
 #[cfg_attr(feature = "strum", derive(strum::EnumIter))]
 pub enum CharacterFlag {
     UnloadDlcCostume = 0,
@@ -96,12 +98,40 @@ impl Character {
         &mut self.class_inventory[usize::try_from(class_id.get() - 1).unwrap()]
     }
 
+    pub(crate) fn classes_mut(&mut self) -> impl Iterator<Item = &mut CharacterClass> {
+        self.class_inventory.iter_mut()
+    }
+
     pub fn is_flag_set(&self, flag: CharacterFlag) -> bool {
-        self.flags.get(flag as usize).unwrap() != 0
+        self.flags.is_set(flag)
     }
 
     pub fn set_flag(&mut self, flag: CharacterFlag, value: bool) {
-        self.flags.set(flag as usize, u8::from(value).into())
+        self.flags.set(flag, value)
+    }
+}
+
+impl CharacterSets {
+    /// Returns whether the character can be added to the party.
+    ///
+    /// Character IDs that are out of bounds are never selectable.
+    pub fn is_selectable(&self, character_id: u16) -> bool {
+        Self::is_set(&self.selectable_characters, character_id)
+    }
+
+    pub fn is_permanent(&self, character_id: u16) -> bool {
+        Self::is_set(&self.permanent_characters, character_id)
+    }
+
+    pub fn is_temporary(&self, character_id: u16) -> bool {
+        Self::is_set(&self.temporary_characters, character_id)
+    }
+
+    fn is_set(set: &BitFlags<1, 2>, character_id: u16) -> bool {
+        usize::from(character_id)
+            .checked_sub(1)
+            .and_then(|bit| set.get(bit))
+            .is_some_and(|v| v != 0)
     }
 }
 
@@ -0,0 +1,239 @@
+use crate::{
+    chrono::{ChronologicalList, ChronologicalOrder},
+    error::SaveError,
+    util::FixVec,
+    SaveData, SaveResult,
+};
+
+use super::{CharacterSets, CHARACTER_MAX, PARTY_GUEST_MAX, PARTY_MAX};
+
+/// Edits the active party, keeping it consistent with [`CharacterSets`].
+///
+/// Only selectable characters can join the party, and the controlled character
+/// keeps pointing to the same member when the party is rearranged. If the
+/// controlled character leaves the party, control goes back to the leader.
+pub struct PartyEditor<'a> {
+    members: &'a mut FixVec<u16, PARTY_MAX>,
+    guests: &'a mut FixVec<u16, PARTY_GUEST_MAX>,
+    controlled_idx: &'a mut u16,
+    sets: &'a mut CharacterSets,
+    unlock_order: &'a mut ChronologicalList<64>,
+}
+
+/// How a character joins the party when it is unlocked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharacterUnlock {
+    /// The character is fully unlocked.
+    Permanent,
+    /// The character can only join the party temporarily.
+    Temporary,
+}
+
+impl<'a> PartyEditor<'a> {
+    pub fn new(save: &'a mut SaveData) -> Self {
+        Self {
+            members: &mut save.party_characters,
+            guests: &mut save.party_guests,
+            controlled_idx: &mut save.controlled_character_idx,
+            sets: &mut save.character_sets,
+            unlock_order: &mut save.chronological_data.unlocked_characters,
+        }
+    }
+
+    /// Returns the character IDs of the party members, starting from the leader.
+    pub fn members(&self) -> impl Iterator<Item = u16> + '_ {
+        self.members.iter().copied()
+    }
+
+    /// Returns the guest IDs (from `FLD_NpcList`) of the party.
+    pub fn guests(&self) -> impl Iterator<Item = u16> + '_ {
+        self.guests.iter().copied()
+    }
+
+    pub fn character_sets(&self) -> &CharacterSets {
+        self.sets
+    }
+
+    /// Returns the character ID of the party leader, i.e. the first member.
+    pub fn leader(&self) -> Option<u16> {
+        self.members.get(0).copied()
+    }
+
+    /// Returns the character ID of the controlled character.
+    pub fn controlled(&self) -> Option<u16> {
+        self.members
+            .get(usize::from(*self.controlled_idx))
+            .or_else(|| self.members.get(0))
+            .copied()
+    }
+
+    /// Returns whether the character is a party member.
+    pub fn contains(&self, character_id: u16) -> bool {
+        self.position(character_id).is_some()
+    }
+
+    /// Adds a character at the end of the party.
+    ///
+    /// ## Errors
+    /// Fails if the character is not selectable, is already a member, or if
+    /// the party is full.
+    pub fn add(&mut self, character_id: u16) -> SaveResult<()> {
+        if !self.sets.is_selectable(character_id) {
+            return Err(SaveError::UnselectableCharacter(character_id));
+        }
+        if self.contains(character_id) {
+            return Err(SaveError::AlreadyInParty(character_id));
+        }
+        self.members
+            .try_push(character_id)
+            .map_err(|_| SaveError::PartyFull(character_id))
+    }
+
+    /// Removes a character from the party.
+    ///
+    /// ## Errors
+    /// Fails if the character is not a party member, or if it is the last
+    /// member, as the party can't be empty.
+    pub fn remove(&mut self, character_id: u16) -> SaveResult<()> {
+        let pos = self
+            .position(character_id)
+            .ok_or(SaveError::NotInParty(character_id))?;
+        if self.members.len() == 1 {
+            return Err(SaveError::LastPartyMember(character_id));
+        }
+        self.rearrange(|members| {
+            members.remove(pos);
+        });
+        Ok(())
+    }
+
+    /// Moves a party member to the given position, shifting the members in between.
+    ///
+    /// ## Errors
+    /// Fails if the character is not a party member.
+    ///
+    /// ## Panics
+    /// Panics if the position is out of bounds for the party.
+    pub fn move_member(&mut self, character_id: u16, index: usize) -> SaveResult<()> {
+        assert!(index < self.members.len(), "index out of bounds");
+        let pos = self
+            .position(character_id)
+            .ok_or(SaveError::NotInParty(character_id))?;
+        self.rearrange(|members| {
+            let id = members.remove(pos);
+            // Can't fail, the member was just removed
+            members.try_insert(index, id).unwrap();
+        });
+        Ok(())
+    }
+
+    /// Makes a party member the party leader, by moving it to the front of the party.
+    ///
+    /// ## Errors
+    /// Fails if the character is not a party member.
+    pub fn set_leader(&mut self, character_id: u16) -> SaveResult<()> {
+        self.move_member(character_id, 0)
+    }
+
+    /// Takes control of a party member.
+    ///
+    /// ## Errors
+    /// Fails if the character is not a party member.
+    pub fn set_controlled(&mut self, character_id: u16) -> SaveResult<()> {
+        let pos = self
+            .position(character_id)
+            .ok_or(SaveError::NotInParty(character_id))?;
+        *self.controlled_idx = pos.try_into().unwrap();
+        Ok(())
+    }
+
+    /// Adds a guest to the party. The ID is from `FLD_NpcList`.
+    ///
+    /// ## Errors
+    /// Fails if the guest is already in the party, or if there are no free
+    /// guest slots.
+    pub fn add_guest(&mut self, npc_id: u16) -> SaveResult<()> {
+        if self.guests.iter().any(|&id| id == npc_id) {
+            return Err(SaveError::AlreadyInParty(npc_id));
+        }
+        self.guests
+            .try_push(npc_id)
+            .map_err(|_| SaveError::PartyFull(npc_id))
+    }
+
+    /// Removes a guest from the party. The ID is from `FLD_NpcList`.
+    ///
+    /// ## Errors
+    /// Fails if the guest is not in the party.
+    pub fn remove_guest(&mut self, npc_id: u16) -> SaveResult<()> {
+        let pos = self
+            .guests
+            .iter()
+            .position(|&id| id == npc_id)
+            .ok_or(SaveError::NotInParty(npc_id))?;
+        self.guests.remove(pos);
+        Ok(())
+    }
+
+    /// Unlocks a character, making it selectable.
+    ///
+    /// If the character was never unlocked before, it is added to the character unlock
+    /// order ([`ChronologicalData::unlocked_characters`]) as the most recent entry.
+    ///
+    /// ## Panics
+    /// Panics if the character ID is out of bounds (`0` or above 64).
+    ///
+    /// [`ChronologicalData::unlocked_characters`]: crate::chrono::ChronologicalData::unlocked_characters
+    pub fn unlock_character(&mut self, character_id: u16, unlock: CharacterUnlock) {
+        let bit = character_bit(character_id);
+        let permanent = unlock == CharacterUnlock::Permanent;
+        self.sets.selectable_characters.set(bit, 1);
+        self.sets.permanent_characters.set(bit, permanent.into());
+        self.sets.temporary_characters.set(bit, (!permanent).into());
+        if !self.unlock_order.is_present(bit) {
+            self.unlock_order.insert(bit);
+        }
+    }
+
+    /// Locks a character, removing it from the party if needed.
+    ///
+    /// The character keeps its position in the unlock order.
+    ///
+    /// ## Errors
+    /// Fails if the character is the last party member. The character is left
+    /// unlocked in that case.
+    ///
+    /// ## Panics
+    /// Panics if the character ID is out of bounds (`0` or above 64).
+    pub fn lock_character(&mut self, character_id: u16) -> SaveResult<()> {
+        let bit = character_bit(character_id);
+        if self.contains(character_id) {
+            self.remove(character_id)?;
+        }
+        self.sets.selectable_characters.set(bit, 0);
+        self.sets.permanent_characters.set(bit, 0);
+        self.sets.temporary_characters.set(bit, 0);
+        Ok(())
+    }
+
+    fn position(&self, character_id: u16) -> Option<usize> {
+        self.members.iter().position(|&id| id == character_id)
+    }
+
+    /// Edits the member list, then updates the controlled index so it points to
+    /// the same character, or to the leader if it left the party.
+    fn rearrange(&mut self, edit: impl FnOnce(&mut FixVec<u16, PARTY_MAX>)) {
+        let controlled = self.controlled();
+        edit(self.members);
+        let pos = controlled.and_then(|id| self.position(id)).unwrap_or(0);
+        *self.controlled_idx = pos.try_into().unwrap();
+    }
+}
+
+/// Converts a character ID to its bit index in [`CharacterSets`].
+fn character_bit(character_id: u16) -> usize {
+    usize::from(character_id)
+        .checked_sub(1)
+        .filter(|&bit| bit < CHARACTER_MAX)
+        .expect("character ID out of bounds")
+}
@@ -0,0 +1,604 @@
+//! Save-independent format for party formations, used to share builds.
+//!
+//! Portable formations can be serialized as a compact share code, or (with the
+//! `serde` feature) in any serde format, e.g. JSON.
+//!
+//! Unlike [`PartyFormation`], accessories are referenced by item ID rather than
+//! by inventory slot, so formations can be imported into a different save file.
+
+use std::io::{Cursor, Read};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use thiserror::Error;
+
+use crate::{
+    dlc::CRAFTED_ITEM_ID,
+    flags::Flag,
+    item::{ItemSlot, ItemType},
+    SaveData,
+};
+
+use super::{
+    class::{
+        CharacterClass, ClassAccessory, ClassFlags, CHARACTER_CLASS_ACCESSORY_MAX,
+        CHARACTER_CLASS_ART_MAX, CHARACTER_CLASS_GEM_MAX, CHARACTER_CLASS_SKILL_MAX,
+    },
+    formation::{CharacterFormation, FormationName, PartyFormation},
+    slot::EmptySlotMut,
+    CHARACTER_CLASS_MAX, CHARACTER_MAX, OUROBOROS_ART_MAX, OUROBOROS_MAX, OUROBOROS_SKILL_MAX,
+    PARTY_MAX,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PortableFormation {
+    /// Format version, see [`PortableFormation::VERSION`]
+    pub version: u8,
+    pub name: PortableName,
+    /// Character IDs in the party
+    pub party: Vec<u16>,
+    pub characters: Vec<PortableCharacter>,
+    pub ouroboros: Vec<PortableOuroboros>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PortableName {
+    pub name_id: u16,
+    pub number: u16,
+    pub color_id: u16,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PortableCharacter {
+    pub character_id: u16,
+    /// The selected class, `0` if none
+    pub class_id: u16,
+    pub class: PortableClass,
+    pub costume_id: u16,
+    pub attachment: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PortableClass {
+    pub cp: u32,
+    pub unlock_points: u16,
+    pub rank: u8,
+    /// Raw [`ClassFlags`] bits
+    pub flags: u8,
+    pub gems: [Option<u8>; CHARACTER_CLASS_GEM_MAX],
+    pub arts: [Option<u16>; CHARACTER_CLASS_ART_MAX],
+    pub skills: [Option<u16>; CHARACTER_CLASS_SKILL_MAX],
+    pub accessories: [Option<PortableAccessory>; CHARACTER_CLASS_ACCESSORY_MAX],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PortableAccessory {
+    /// An accessory from the inventory, referenced by item ID
+    Inventory(u16),
+    /// An accessory that does not refer to the inventory (e.g. heroes' default
+    /// accessories), stored as-is
+    Builtin {
+        bdat_id: u16,
+        slot_index: u16,
+        item_type: u16,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PortableOuroboros {
+    pub ouroboros_id: u16,
+    pub arts: [u16; OUROBOROS_ART_MAX],
+    pub linked_skills: [u16; OUROBOROS_SKILL_MAX],
+}
+
+/// The result of importing a portable formation into a save file.
+#[derive(Debug)]
+pub struct ResolvedFormation {
+    /// The formation, with accessories pointing to the save's inventory
+    pub formation: PartyFormation,
+    /// Accessories that could not be found in the inventory. They are left
+    /// unequipped in [`ResolvedFormation::formation`].
+    pub missing: Vec<MissingAccessory>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissingAccessory {
+    pub character_id: u16,
+    /// Index of the class accessory slot
+    pub slot: usize,
+    pub item_id: u16,
+}
+
+#[derive(Error, Debug)]
+pub enum PortableError {
+    #[error("Share code is not valid base64")]
+    InvalidEncoding(#[from] base64::DecodeError),
+    #[error("Share code is truncated")]
+    Truncated,
+    #[error("Share code has {0} unexpected trailing bytes")]
+    TrailingBytes(usize),
+    #[error("Invalid accessory tag {0}")]
+    InvalidAccessory(u8),
+    #[error(
+        "Unsupported formation version {0} (only version {} is supported)",
+        PortableFormation::VERSION
+    )]
+    UnsupportedVersion(u8),
+    #[error("Too many entries in list ({0}, max 255)")]
+    TooLong(usize),
+    #[error("Too many party members ({0}, max {})", PARTY_MAX)]
+    PartyTooLarge(usize),
+    #[error("Invalid character ID {0}")]
+    InvalidCharacter(u16),
+    #[error("Invalid class ID {0}")]
+    InvalidClass(u16),
+    #[error("Invalid Ouroboros ID {0}")]
+    InvalidOuroboros(u16),
+    #[error("Character {0} is in the party more than once")]
+    DuplicateCharacter(u16),
+    #[error("Character {0} can't be added to the party in this save")]
+    UnselectableCharacter(u16),
+    #[error(
+        "Accessory slot {slot} of character {character_id} is builtin but refers to the inventory"
+    )]
+    InvalidBuiltinAccessory { character_id: u16, slot: usize },
+}
+
+const ACCESSORY_NONE: u8 = 0;
+const ACCESSORY_INVENTORY: u8 = 1;
+const ACCESSORY_BUILTIN: u8 = 2;
+
+impl PortableFormation {
+    /// The current format version
+    pub const VERSION: u8 = 1;
+
+    /// Exports a party formation. Only characters and Ouroboros that have a slot in
+    /// the formation are included.
+    pub fn from_formation(formation: &PartyFormation) -> Self {
+        Self {
+            version: Self::VERSION,
+            name: PortableName {
+                name_id: formation.name.name_id,
+                number: formation.name.number,
+                color_id: formation.name.color_id,
+            },
+            party: formation.party.iter().copied().collect(),
+            characters: formation
+                .characters
+                .iter()
+                .filter(|c| c.character_id != 0)
+                .map(PortableCharacter::from_formation)
+                .collect(),
+            ouroboros: formation
+                .ouroboros
+                .iter()
+                .filter(|o| o.ouroboros_id != 0)
+                .map(|o| PortableOuroboros {
+                    ouroboros_id: o.ouroboros_id,
+                    arts: o.art_ids,
+                    linked_skills: o.linked_skills,
+                })
+                .collect(),
+        }
+    }
+
+    /// Imports the formation into a save file, finding equipped accessories in
+    /// the save's inventory.
+    ///
+    /// Crafted accessories all share the same item ID, so they can't be found
+    /// and are always reported as missing.
+    ///
+    /// ## Errors
+    /// Returns an error if the formation's version is not supported, if the party
+    /// is too large, contains duplicates or characters that are not selectable
+    /// in `save`, if a character, class or Ouroboros ID is out of bounds, or if a
+    /// builtin accessory refers to the inventory.
+    pub fn resolve(&self, save: &SaveData) -> Result<ResolvedFormation, PortableError> {
+        self.validate()?;
+        if let Some(&id) = self
+            .party
+            .iter()
+            .find(|&&id| !save.character_sets.is_selectable(id))
+        {
+            return Err(PortableError::UnselectableCharacter(id));
+        }
+
+        let mut formation = PartyFormation {
+            name: FormationName {
+                name_id: self.name.name_id,
+                number: self.name.number,
+                color_id: self.name.color_id,
+            },
+            ..Default::default()
+        };
+        for &char_id in &self.party {
+            // Can't fail, the party size was validated
+            formation.party.try_push(char_id).unwrap();
+        }
+
+        let mut missing = Vec::new();
+        for portable in &self.characters {
+            let slot = formation.character_mut(portable.character_id);
+            slot.current_class = portable.class_id;
+            slot.costume_id = portable.costume_id;
+            slot.attachment = portable.attachment;
+            portable
+                .class
+                .resolve(&mut slot.class, save, |slot, item_id| {
+                    missing.push(MissingAccessory {
+                        character_id: portable.character_id,
+                        slot,
+                        item_id,
+                    })
+                });
+        }
+
+        for portable in &self.ouroboros {
+            let slot = formation.ouroboros_mut(portable.ouroboros_id);
+            slot.art_ids = portable.arts;
+            slot.linked_skills = portable.linked_skills;
+        }
+
+        Ok(ResolvedFormation { formation, missing })
+    }
+
+    /// Checks the version, list lengths and IDs, as portable formations may come from
+    /// untrusted sources.
+    ///
+    /// IDs are unique keys for formation slots, so if they are in bounds, there is
+    /// always a slot for each character and Ouroboros.
+    fn validate(&self) -> Result<(), PortableError> {
+        if self.version != Self::VERSION {
+            return Err(PortableError::UnsupportedVersion(self.version));
+        }
+        if self.party.len() > PARTY_MAX {
+            return Err(PortableError::PartyTooLarge(self.party.len()));
+        }
+        let mut character_ids = self
+            .party
+            .iter()
+            .chain(self.characters.iter().map(|c| &c.character_id));
+        if let Some(&id) = character_ids.find(|&&id| !is_valid_id(id, CHARACTER_MAX)) {
+            return Err(PortableError::InvalidCharacter(id));
+        }
+        if let Some((_, &id)) = self
+            .party
+            .iter()
+            .enumerate()
+            .find(|&(i, id)| self.party[..i].contains(id))
+        {
+            return Err(PortableError::DuplicateCharacter(id));
+        }
+        if let Some(c) = self
+            .characters
+            .iter()
+            .find(|c| c.class_id != 0 && !is_valid_id(c.class_id, CHARACTER_CLASS_MAX))
+        {
+            return Err(PortableError::InvalidClass(c.class_id));
+        }
+        if let Some(o) = self
+            .ouroboros
+            .iter()
+            .find(|o| !is_valid_id(o.ouroboros_id, OUROBOROS_MAX))
+        {
+            return Err(PortableError::InvalidOuroboros(o.ouroboros_id));
+        }
+        // Builtin accessories are written as-is, so they must not point to an
+        // inventory slot that may not exist in the target save
+        for c in &self.characters {
+            for (slot, accessory) in c.class.accessories.iter().enumerate() {
+                if let Some(PortableAccessory::Builtin {
+                    bdat_id,
+                    slot_index,
+                    item_type,
+                }) = *accessory
+                {
+                    if ClassAccessory::new_raw(bdat_id, slot_index, item_type)
+                        .is_inventory_accessory()
+                    {
+                        return Err(PortableError::InvalidBuiltinAccessory {
+                            character_id: c.character_id,
+                            slot,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Encodes the formation as a compact, URL-safe share code.
+    ///
+    /// ## Errors
+    /// Returns an error if one of the formation's lists has more than 255 entries.
+    pub fn to_share_code(&self) -> Result<String, PortableError> {
+        let mut out = vec![self.version];
+        write_u16s(
+            &mut out,
+            &[self.name.name_id, self.name.number, self.name.color_id],
+        );
+
+        write_len(&mut out, self.party.len())?;
+        write_u16s(&mut out, &self.party);
+
+        write_len(&mut out, self.characters.len())?;
+        for character in &self.characters {
+            write_u16s(
+                &mut out,
+                &[
+                    character.character_id,
+                    character.class_id,
+                    character.costume_id,
+                ],
+            );
+            out.push(character.attachment);
+            character.class.write(&mut out);
+        }
+
+        write_len(&mut out, self.ouroboros.len())?;
+        for ouroboros in &self.ouroboros {
+            write_u16s(&mut out, &[ouroboros.ouroboros_id]);
+            write_u16s(&mut out, &ouroboros.arts);
+            write_u16s(&mut out, &ouroboros.linked_skills);
+        }
+
+        Ok(URL_SAFE_NO_PAD.encode(out))
+    }
+
+    /// Decodes a share code created by [`PortableFormation::to_share_code`].
+    ///
+    /// ## Errors
+    /// Returns an error if the share code is malformed, or if its version is
+    /// not supported.
+    pub fn from_share_code(code: &str) -> Result<Self, PortableError> {
+        let bytes = URL_SAFE_NO_PAD.decode(code.trim())?;
+        let mut reader = Cursor::new(bytes.as_slice());
+
+        let version = reader.read_u8()?;
+        if version != Self::VERSION {
+            return Err(PortableError::UnsupportedVersion(version));
+        }
+        let name = PortableName {
+            name_id: reader.read_u16::<LE>()?,
+            number: reader.read_u16::<LE>()?,
+            color_id: reader.read_u16::<LE>()?,
+        };
+
+        let party_len = reader.read_u8()?;
+        let party = (0..party_len)
+            .map(|_| reader.read_u16::<LE>())
+            .collect::<Result<_, _>>()?;
+
+        let char_len = reader.read_u8()?;
+        let characters = (0..char_len)
+            .map(|_| {
+                Ok(PortableCharacter {
+                    character_id: reader.read_u16::<LE>()?,
+                    class_id: reader.read_u16::<LE>()?,
+                    costume_id: reader.read_u16::<LE>()?,
+                    attachment: reader.read_u8()?,
+                    class: PortableClass::read(&mut reader)?,
+                })
+            })
+            .collect::<Result<_, PortableError>>()?;
+
+        let ouro_len = reader.read_u8()?;
+        let ouroboros = (0..ouro_len)
+            .map(|_| {
+                let ouroboros_id = reader.read_u16::<LE>()?;
+                let mut arts = [0; OUROBOROS_ART_MAX];
+                let mut linked_skills = [0; OUROBOROS_SKILL_MAX];
+                reader.read_u16_into::<LE>(&mut arts)?;
+                reader.read_u16_into::<LE>(&mut linked_skills)?;
+                Ok(PortableOuroboros {
+                    ouroboros_id,
+                    arts,
+                    linked_skills,
+                })
+            })
+            .collect::<Result<_, PortableError>>()?;
+
+        let trailing = reader.bytes().count();
+        if trailing != 0 {
+            return Err(PortableError::TrailingBytes(trailing));
+        }
+
+        Ok(Self {
+            version,
+            name,
+            party,
+            characters,
+            ouroboros,
+        })
+    }
+}
+
+impl PortableCharacter {
+    fn from_formation(formation: &CharacterFormation) -> Self {
+        Self {
+            character_id: formation.character_id,
+            class_id: formation.current_class,
+            class: PortableClass::from_class(&formation.class),
+            costume_id: formation.costume_id,
+            attachment: formation.attachment,
+        }
+    }
+}
+
+impl PortableClass {
+    fn from_class(class: &CharacterClass) -> Self {
+        Self {
+            cp: class.cp,
+            unlock_points: class.unlock_points,
+            rank: class.level,
+            flags: class.flags.iter().fold(0, |bits, f| bits | 1 << f.index()),
+            gems: std::array::from_fn(|i| class.gem_slot(i).get()),
+            arts: std::array::from_fn(|i| class.art_slot(i).get()),
+            skills: std::array::from_fn(|i| class.skill_slot(i).get()),
+            accessories: std::array::from_fn(|i| {
+                let accessory = class.accessory_slot(i).get()?;
+                Some(if accessory.is_inventory_accessory() {
+                    PortableAccessory::Inventory(accessory.bdat_id())
+                } else {
+                    PortableAccessory::Builtin {
+                        bdat_id: accessory.bdat_id(),
+                        slot_index: accessory.slot_index(),
+                        item_type: accessory.raw_item_type(),
+                    }
+                })
+            }),
+        }
+    }
+
+    /// Writes the class data, looking up inventory accessories in the save.
+    /// Accessories that can't be found are passed to `missing`.
+    fn resolve(
+        &self,
+        class: &mut CharacterClass,
+        save: &SaveData,
+        mut missing: impl FnMut(usize, u16),
+    ) {
+        class.cp = self.cp;
+        class.unlock_points = self.unlock_points;
+        class.level = self.rank;
+        for flag in ClassFlags::ALL {
+            class
+                .flags
+                .set(*flag, self.flags & (1 << flag.index()) != 0);
+        }
+        for (i, gem) in self.gems.iter().enumerate() {
+            class.gem_slot_mut(i).set(*gem);
+        }
+        for (i, art) in self.arts.iter().enumerate() {
+            class.art_slot_mut(i).set(*art);
+        }
+        for (i, skill) in self.skills.iter().enumerate() {
+            class.skill_slot_mut(i).set(*skill);
+        }
+
+        for (i, accessory) in self.accessories.iter().enumerate() {
+            let mut slot = class.accessory_slot_mut(i);
+            match *accessory {
+                None => slot.set_empty(),
+                Some(PortableAccessory::Builtin {
+                    bdat_id,
+                    slot_index,
+                    item_type,
+                }) => slot.set(Some(ClassAccessory::new_raw(
+                    bdat_id, slot_index, item_type,
+                ))),
+                Some(PortableAccessory::Inventory(item_id)) => {
+                    match find_accessory(save, item_id) {
+                        Some(inventory_slot) => slot.set_from_inventory(inventory_slot),
+                        None => {
+                            slot.set_empty();
+                            missing(i, item_id);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        out.write_u32::<LE>(self.cp).unwrap();
+        write_u16s(out, &[self.unlock_points]);
+        out.extend([self.rank, self.flags]);
+        out.extend(self.gems.map(|g| g.unwrap_or(u8::MAX)));
+        write_u16s(out, &self.arts.map(|a| a.unwrap_or(u16::MAX)));
+        write_u16s(out, &self.skills.map(|s| s.unwrap_or(u16::MAX)));
+        for accessory in &self.accessories {
+            match *accessory {
+                None => out.push(ACCESSORY_NONE),
+                Some(PortableAccessory::Inventory(item_id)) => {
+                    out.push(ACCESSORY_INVENTORY);
+                    write_u16s(out, &[item_id]);
+                }
+                Some(PortableAccessory::Builtin {
+                    bdat_id,
+                    slot_index,
+                    item_type,
+                }) => {
+                    out.push(ACCESSORY_BUILTIN);
+                    write_u16s(out, &[bdat_id, slot_index, item_type]);
+                }
+            }
+        }
+    }
+
+    fn read(reader: &mut Cursor<&[u8]>) -> Result<Self, PortableError> {
+        let cp = reader.read_u32::<LE>()?;
+        let unlock_points = reader.read_u16::<LE>()?;
+        let rank = reader.read_u8()?;
+        let flags = reader.read_u8()?;
+        let mut gems = [0; CHARACTER_CLASS_GEM_MAX];
+        let mut arts = [0; CHARACTER_CLASS_ART_MAX];
+        let mut skills = [0; CHARACTER_CLASS_SKILL_MAX];
+        reader.read_exact(&mut gems)?;
+        reader.read_u16_into::<LE>(&mut arts)?;
+        reader.read_u16_into::<LE>(&mut skills)?;
+        let mut accessories = [None; CHARACTER_CLASS_ACCESSORY_MAX];
+        for accessory in &mut accessories {
+            *accessory = match reader.read_u8()? {
+                ACCESSORY_NONE => None,
+                ACCESSORY_INVENTORY => Some(PortableAccessory::Inventory(reader.read_u16::<LE>()?)),
+                ACCESSORY_BUILTIN => Some(PortableAccessory::Builtin {
+                    bdat_id: reader.read_u16::<LE>()?,
+                    slot_index: reader.read_u16::<LE>()?,
+                    item_type: reader.read_u16::<LE>()?,
+                }),
+                tag => return Err(PortableError::InvalidAccessory(tag)),
+            };
+        }
+        Ok(Self {
+            cp,
+            unlock_points,
+            rank,
+            flags,
+            gems: gems.map(|g| (g != u8::MAX).then_some(g)),
+            arts: arts.map(|a| (a != u16::MAX).then_some(a)),
+            skills: skills.map(|s| (s != u16::MAX).then_some(s)),
+            accessories,
+        })
+    }
+}
+
+impl From<std::io::Error> for PortableError {
+    fn from(_: std::io::Error) -> Self {
+        // Reads are only done from memory, so the only possible error is EOF
+        Self::Truncated
+    }
+}
+
+/// Returns whether an ID is in the `1..=max` range.
+fn is_valid_id(id: u16, max: usize) -> bool {
+    (1..=max).contains(&usize::from(id))
+}
+
+fn find_accessory(save: &SaveData, item_id: u16) -> Option<&ItemSlot> {
+    if item_id == CRAFTED_ITEM_ID {
+        return None;
+    }
+    save.inventory
+        .slots(ItemType::Accessory)
+        .iter()
+        .find(|s| s.is_valid() && s.item_id() == item_id)
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) -> Result<(), PortableError> {
+    out.push(u8::try_from(len).map_err(|_| PortableError::TooLong(len))?);
+    Ok(())
+}
+
+fn write_u16s(out: &mut Vec<u8>, values: &[u16]) {
+    for &v in values {
+        out.write_u16::<LE>(v).unwrap();
+    }
+}
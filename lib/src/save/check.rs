@@ -0,0 +1,379 @@
+use std::num::NonZeroU32;
+
+use thiserror::Error;
+
+use crate::{
+    character::{
+        class::{CharacterClass, ClassAccessory, CHARACTER_CLASS_ACCESSORY_MAX},
+        slot::{EmptySlotMut, SlotMut},
+        CHARACTER_CLASS_MAX,
+    },
+    item::{Inventory, ItemType, SlotFlags},
+    SaveData,
+};
+
+/// Inventory arrays, along with their field names.
+const INVENTORY_FIELDS: [(ItemType, &str); 8] = [
+    (ItemType::Cylinder, "cylinders"),
+    (ItemType::Gem, "gems"),
+    (ItemType::Collection, "collectibles"),
+    (ItemType::Info, "infos"),
+    (ItemType::Accessory, "accessories"),
+    (ItemType::Precious, "key_items"),
+    (ItemType::Exchange, "exchange"),
+    (ItemType::Extra, "extra"),
+];
+
+/// An inconsistency between different sections of the save file, as reported
+/// by [`SaveData::check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// Path to the inconsistent field, e.g. `inventory.accessories[12]`
+    pub path: String,
+    pub problem: Problem,
+    target: Target,
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Problem {
+    #[error("item slot should have crafted accessory data, but has no crafting entry")]
+    MissingCraftData,
+    #[error("slot index {0} does not match the slot's position")]
+    SlotIndexMismatch(u16),
+    #[error("chronological ID {id} is greater than the current maximum ({max})")]
+    ChronologicalIdAboveMax { id: u32, max: u32 },
+    #[error("accessory points to inventory slot {0}, which is empty or out of bounds")]
+    AccessorySlotEmpty(u16),
+    #[error("accessory ID {bdat_id} does not match item {item_id} in inventory slot {slot}")]
+    AccessoryMismatch {
+        slot: u16,
+        bdat_id: u16,
+        item_id: u16,
+    },
+    #[error("character {0} is in the party, but is not selectable")]
+    UnselectableCharacter(u16),
+    #[error("controlled character index {index} is out of bounds for a party of {len}")]
+    ControlledCharacterOutOfBounds { index: u16, len: usize },
+}
+
+/// Internal location and kind of a finding, used for repairs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    CraftData(ItemType, usize),
+    SlotIndex(ItemType, usize),
+    InventoryChronologicalId(u32),
+    ChronologicalData,
+    ClassAccessory {
+        character: usize,
+        class: usize,
+        accessory: usize,
+        problem: AccessoryProblem,
+    },
+    FormationAccessory {
+        formation: usize,
+        character: usize,
+        accessory: usize,
+        problem: AccessoryProblem,
+    },
+    PartyMember(usize),
+    ControlledCharacter,
+}
+
+/// Problems with an equipped accessory, see [`check_class`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AccessoryProblem {
+    SlotEmpty(u16),
+    Mismatch {
+        slot: u16,
+        bdat_id: u16,
+        item_id: u16,
+    },
+}
+
+impl Problem {
+    /// Returns a description of the fix that [`SaveData::repair`] applies for
+    /// this problem.
+    pub fn suggested_fix(&self) -> &'static str {
+        match self {
+            Self::MissingCraftData => {
+                "initialize crafted data with default values, or clear the crafted data flag \
+                if the item is not a crafted accessory"
+            }
+            Self::SlotIndexMismatch(_) => "set the slot index to the slot's position",
+            Self::ChronologicalIdAboveMax { .. } => "raise the maximum to the highest ID in use",
+            Self::AccessorySlotEmpty(_) => "unequip the accessory",
+            Self::AccessoryMismatch { .. } => "update the accessory ID from the inventory slot",
+            Self::UnselectableCharacter(_) => "remove the character from the party",
+            Self::ControlledCharacterOutOfBounds { .. } => "control the party leader",
+        }
+    }
+}
+
+impl SaveData {
+    /// Checks the save file for inconsistencies between sections that refer to
+    /// each other, e.g. equipped accessories and inventory slots.
+    ///
+    /// This does not require game data, so only structural problems can be found.
+    pub fn check(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        self.check_inventory(&mut findings);
+        self.check_chronological(&mut findings);
+        self.check_accessories(&mut findings);
+        self.check_party(&mut findings);
+        findings
+    }
+
+    /// Checks the save file for inconsistencies, then attempts to fix them.
+    ///
+    /// Returns the problems that were found. See [`Problem::suggested_fix`] for
+    /// the fix applied to each problem.
+    pub fn repair(&mut self) -> Vec<Finding> {
+        let findings = self.check();
+        // Party members are removed last-to-first, so that indices stay valid.
+        for finding in findings.iter().rev() {
+            self.apply_fix(finding);
+        }
+        findings
+    }
+
+    fn check_inventory(&self, findings: &mut Vec<Finding>) {
+        let max = self.inventory.chronological_id_max;
+        for (item_type, field) in INVENTORY_FIELDS {
+            for (i, slot) in self.inventory.slots(item_type).iter().enumerate() {
+                if !slot.is_valid() {
+                    continue;
+                }
+                let mut report = |problem, target| {
+                    findings.push(Finding {
+                        path: format!("inventory.{field}[{i}]"),
+                        problem,
+                        target,
+                    })
+                };
+                // Crafting data is keyed by the slot's position
+                if (slot.is_crafted_accessory() || slot.is_flag_set(SlotFlags::HasCraftData))
+                    && self.accessory_crafting.get_data(i).is_none()
+                {
+                    report(Problem::MissingCraftData, Target::CraftData(item_type, i));
+                }
+                if usize::from(slot.index()) != i {
+                    report(
+                        Problem::SlotIndexMismatch(slot.index()),
+                        Target::SlotIndex(item_type, i),
+                    );
+                }
+                if slot.chronological_id() > max {
+                    let id = slot.chronological_id();
+                    report(
+                        Problem::ChronologicalIdAboveMax { id, max },
+                        Target::InventoryChronologicalId(id),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_chronological(&self, findings: &mut Vec<Finding>) {
+        for overflow in self.chronological_data.max_overflows() {
+            findings.push(Finding {
+                path: format!("chronological_data.{}", overflow.field),
+                problem: Problem::ChronologicalIdAboveMax {
+                    id: overflow.highest.into(),
+                    max: overflow.max.into(),
+                },
+                target: Target::ChronologicalData,
+            });
+        }
+    }
+
+    fn check_accessories(&self, findings: &mut Vec<Finding>) {
+        for (c, character) in self.characters.iter().enumerate() {
+            for class in 0..CHARACTER_CLASS_MAX {
+                let class_id = NonZeroU32::new(u32::try_from(class).unwrap() + 1).unwrap();
+                check_class(
+                    &self.inventory,
+                    character.class_data(class_id),
+                    |a, problem| {
+                        findings.push(Finding {
+                            path: format!(
+                                "characters[{c}].class_inventory[{class}].accessories[{a}]"
+                            ),
+                            problem: problem.into(),
+                            target: Target::ClassAccessory {
+                                character: c,
+                                class,
+                                accessory: a,
+                                problem,
+                            },
+                        })
+                    },
+                );
+            }
+        }
+
+        for (f, formation) in self.party_formations.iter().enumerate() {
+            if !formation.is_valid() {
+                continue;
+            }
+            for (c, character) in formation.characters.iter().enumerate() {
+                check_class(&self.inventory, &character.class, |a, problem| {
+                    findings.push(Finding {
+                        path: format!(
+                            "party_formations[{f}].characters[{c}].class.accessories[{a}]"
+                        ),
+                        problem: problem.into(),
+                        target: Target::FormationAccessory {
+                            formation: f,
+                            character: c,
+                            accessory: a,
+                            problem,
+                        },
+                    })
+                });
+            }
+        }
+    }
+
+    fn check_party(&self, findings: &mut Vec<Finding>) {
+        for (i, &char_id) in self.party_characters.iter().enumerate() {
+            if !self.character_sets.is_selectable(char_id) {
+                findings.push(Finding {
+                    path: format!("party_characters[{i}]"),
+                    problem: Problem::UnselectableCharacter(char_id),
+                    target: Target::PartyMember(i),
+                });
+            }
+        }
+
+        let len = self.party_characters.len();
+        let index = self.controlled_character_idx;
+        if usize::from(index) >= len && len != 0 {
+            findings.push(Finding {
+                path: "controlled_character_idx".to_string(),
+                problem: Problem::ControlledCharacterOutOfBounds { index, len },
+                target: Target::ControlledCharacter,
+            });
+        }
+    }
+
+    fn apply_fix(&mut self, finding: &Finding) {
+        match finding.target {
+            Target::CraftData(item_type, i) => {
+                let has_data = self.inventory.slots(item_type)[i].is_crafted_accessory()
+                    && self
+                        .accessory_crafting
+                        .set_data(i, Default::default())
+                        .is_ok();
+                self.inventory.slots_mut(item_type)[i].set_flag(SlotFlags::HasCraftData, has_data);
+            }
+            Target::SlotIndex(item_type, i) => {
+                self.inventory.slots_mut(item_type)[i].set_index(i.try_into().unwrap());
+            }
+            Target::InventoryChronologicalId(id) => {
+                let max = &mut self.inventory.chronological_id_max;
+                *max = (*max).max(id);
+            }
+            Target::ChronologicalData => self.chronological_data.fix_max_overflows(),
+            Target::ClassAccessory {
+                character,
+                class,
+                accessory,
+                problem,
+            } => {
+                let class = self.characters[character]
+                    .class_data_mut(NonZeroU32::new(u32::try_from(class).unwrap() + 1).unwrap());
+                fix_accessory(
+                    &self.inventory,
+                    class.accessory_slot_mut(accessory),
+                    problem,
+                );
+            }
+            Target::FormationAccessory {
+                formation,
+                character,
+                accessory,
+                problem,
+            } => {
+                let class = &mut self.party_formations[formation].characters[character].class;
+                fix_accessory(
+                    &self.inventory,
+                    class.accessory_slot_mut(accessory),
+                    problem,
+                );
+            }
+            Target::PartyMember(i) => {
+                self.party_characters.remove(i);
+                if usize::from(self.controlled_character_idx) >= self.party_characters.len() {
+                    self.controlled_character_idx = 0;
+                }
+            }
+            Target::ControlledCharacter => self.controlled_character_idx = 0,
+        }
+    }
+}
+
+/// Checks the accessories equipped in a class. `report` is called with the accessory
+/// index and the problem that was found.
+pub(crate) fn check_class(
+    inventory: &Inventory,
+    class: &CharacterClass,
+    mut report: impl FnMut(usize, AccessoryProblem),
+) {
+    for a in 0..CHARACTER_CLASS_ACCESSORY_MAX {
+        let Some(accessory) = class.accessory_slot(a).get() else {
+            continue;
+        };
+        if let Some(problem) = check_accessory(inventory, &accessory) {
+            report(a, problem);
+        }
+    }
+}
+
+fn check_accessory(inventory: &Inventory, accessory: &ClassAccessory) -> Option<AccessoryProblem> {
+    if !accessory.is_inventory_accessory() {
+        return None;
+    }
+    let slot_index = accessory.slot_index();
+    let Some(slot) = inventory
+        .accessories
+        .get(usize::from(slot_index))
+        .filter(|s| s.is_valid())
+    else {
+        return Some(AccessoryProblem::SlotEmpty(slot_index));
+    };
+    (slot.item_id() != accessory.bdat_id()).then_some(AccessoryProblem::Mismatch {
+        slot: slot_index,
+        bdat_id: accessory.bdat_id(),
+        item_id: slot.item_id(),
+    })
+}
+
+fn fix_accessory(
+    inventory: &Inventory,
+    mut slot: SlotMut<ClassAccessory>,
+    problem: AccessoryProblem,
+) {
+    match problem {
+        AccessoryProblem::SlotEmpty(_) => slot.set_empty(),
+        AccessoryProblem::Mismatch { slot: index, .. } => {
+            slot.set_from_inventory(&inventory.accessories[usize::from(index)])
+        }
+    }
+}
+
+impl From<AccessoryProblem> for Problem {
+    fn from(problem: AccessoryProblem) -> Self {
+        match problem {
+            AccessoryProblem::SlotEmpty(slot) => Self::AccessorySlotEmpty(slot),
+            AccessoryProblem::Mismatch {
+                slot,
+                bdat_id,
+                item_id,
+            } => Self::AccessoryMismatch {
+                slot,
+                bdat_id,
+                item_id,
+            },
+        }
+    }
+}
@@ -4,7 +4,7 @@ use recordkeeper_macros::SaveBin;
 
 use crate::{
     io::SaveBin,
-    item::{Inventory, ItemType},
+    item::{Inventory, ItemSlot},
     util::FixVec,
 };
 
@@ -55,7 +55,7 @@ pub struct ChronologicalData {
     pub quests: ChronologicalList<600>,
 }
 
-#[derive(SaveBin, Debug)]
+#[derive(SaveBin, Debug, Default, Clone, Copy, PartialEq)]
 pub struct IdSortPair {
     pub id: u16,
     pub sort: u16,
@@ -105,9 +105,16 @@ pub trait ChronologicalOrder {
     fn insert(&mut self, id: usize);
 }
 
+/// Unlock order of classes, arts or skills for a single character.
+///
+/// Entry IDs are art or skill IDs, or class IDs - 1 for class tables. Accessors for
+/// arts or skills share the same counter, so the order is also consistent across
+/// characters.
 pub struct CharacterChronoAccessor<'a, const R: usize, const C: usize> {
     max: &'a mut u16,
     table: CharacterChrono<'a, R, C>,
+    /// Index of the character in the table
+    index: usize,
 }
 
 enum CharacterChrono<'a, const R: usize, const C: usize> {
@@ -120,13 +127,347 @@ struct TableInner<const R: usize, const C: usize> {
     buf: Box<[[u16; R]; C]>,
 }
 
+/// A chronological counter that is lower than some of the entries that use it.
+pub(crate) struct MaxOverflow {
+    /// Name of the field that holds the counter
+    pub(crate) field: &'static str,
+    pub(crate) max: u16,
+    pub(crate) highest: u16,
+}
+
 /// Comparator (descending order) for order keys, making null values greater than every other key.
-struct NullsLastReverse<K: ChronologicalKey>(K);
+pub(crate) struct NullsLastReverse<K: ChronologicalKey>(pub(crate) K);
 
-trait ChronologicalKey: Ord + Copy + SaveBin {
+pub(crate) trait ChronologicalKey: Ord + Copy + SaveBin {
     fn is_null(&self) -> bool;
 }
 
+impl ChronologicalData {
+    /// Returns the class unlock order for a main character. Entry IDs are class IDs - 1.
+    ///
+    /// Characters are indexed like [`ChronologicalData::character_classes`].
+    ///
+    /// ## Panics
+    /// Panics if the character index is out of bounds (`character >= 6`).
+    pub fn character_class_order(
+        &mut self,
+        character: usize,
+    ) -> CharacterChronoAccessor<'_, 64, 6> {
+        CharacterChronoAccessor::new(
+            &mut self.character_classes.max,
+            CharacterChrono::Table(&mut self.character_classes.items),
+            character,
+        )
+    }
+
+    /// Returns the art unlock order for a main character.
+    ///
+    /// Characters are indexed like [`ChronologicalData::character_classes`].
+    ///
+    /// ## Panics
+    /// Panics if the character index is out of bounds (`character >= 6`).
+    pub fn character_arts(&mut self, character: usize) -> CharacterChronoAccessor<'_, 1000, 6> {
+        CharacterChronoAccessor::new(
+            &mut self.art_status_max,
+            CharacterChrono::Table(&mut self.character_art_status),
+            character,
+        )
+    }
+
+    /// Returns the skill unlock order for a main character.
+    ///
+    /// Characters are indexed like [`ChronologicalData::character_classes`].
+    ///
+    /// ## Panics
+    /// Panics if the character index is out of bounds (`character >= 6`).
+    pub fn character_skills(&mut self, character: usize) -> CharacterChronoAccessor<'_, 500, 6> {
+        CharacterChronoAccessor::new(
+            &mut self.skill_status_max,
+            CharacterChrono::Table(&mut self.character_skill_status),
+            character,
+        )
+    }
+
+    /// Returns the art unlock order for a hero.
+    ///
+    /// ## Panics
+    /// Panics if the hero index is out of bounds (`hero >= 58`).
+    pub fn hero_arts(&mut self, hero: usize) -> CharacterChronoAccessor<'_, 64, 58> {
+        CharacterChronoAccessor::new(
+            &mut self.art_status_max,
+            CharacterChrono::Hero(&mut self.hero_art_status),
+            hero,
+        )
+    }
+
+    /// Returns the skill unlock order for a hero.
+    ///
+    /// ## Panics
+    /// Panics if the hero index is out of bounds (`hero >= 58`).
+    pub fn hero_skills(&mut self, hero: usize) -> CharacterChronoAccessor<'_, 64, 58> {
+        CharacterChronoAccessor::new(
+            &mut self.skill_status_max,
+            CharacterChrono::Hero(&mut self.hero_skill_status),
+            hero,
+        )
+    }
+
+    /// Returns the unlock order for Soulhacker arts.
+    pub fn soul_hack_arts(&mut self) -> CharacterChronoAccessor<'_, 100, 1> {
+        CharacterChronoAccessor::new(
+            &mut self.art_status_max,
+            CharacterChrono::Table(&mut self.soul_hack_art_status),
+            0,
+        )
+    }
+
+    /// Returns the unlock order for Soulhacker skills.
+    pub fn soul_hack_skills(&mut self) -> CharacterChronoAccessor<'_, 100, 1> {
+        CharacterChronoAccessor::new(
+            &mut self.skill_status_max,
+            CharacterChrono::Table(&mut self.soul_hack_skill_status),
+            0,
+        )
+    }
+
+    /// Returns the counters whose current maximum is lower than one of their entries.
+    pub(crate) fn max_overflows(&self) -> Vec<MaxOverflow> {
+        self.counters()
+            .into_iter()
+            .filter(|(_, max, highest)| highest > max)
+            .map(|(field, max, highest)| MaxOverflow {
+                field,
+                max,
+                highest,
+            })
+            .collect()
+    }
+
+    /// Raises every counter reported by [`ChronologicalData::max_overflows`] to the
+    /// highest value in use.
+    pub(crate) fn fix_max_overflows(&mut self) {
+        let counters = self.counters();
+        for (max, (_, _, highest)) in self.counters_mut().into_iter().zip(counters) {
+            *max = (*max).max(highest);
+        }
+    }
+
+    /// Returns the name, current maximum and highest entry for each counter.
+    fn counters(&self) -> [(&'static str, u16, u16); 6] {
+        let hero_highest = |tables: &[FixVec<IdSortPair, 64>]| {
+            tables
+                .iter()
+                .flat_map(|t| t.iter().map(|p| p.sort))
+                .max()
+                .unwrap_or_default()
+        };
+        let art_highest = self
+            .character_art_status
+            .highest()
+            .max(self.soul_hack_art_status.highest())
+            .max(hero_highest(&self.hero_art_status));
+        let skill_highest = self
+            .character_skill_status
+            .highest()
+            .max(self.soul_hack_skill_status.highest())
+            .max(hero_highest(&self.hero_skill_status));
+
+        [
+            (
+                "character_classes.max",
+                self.character_classes.max,
+                self.character_classes.items.highest(),
+            ),
+            ("art_status_max", self.art_status_max, art_highest),
+            ("skill_status_max", self.skill_status_max, skill_highest),
+            (
+                "unlocked_characters.max",
+                self.unlocked_characters.max,
+                self.unlocked_characters.items.highest(),
+            ),
+            (
+                "npc_collectopedia.max",
+                self.npc_collectopedia.max,
+                self.npc_collectopedia.items.highest(),
+            ),
+            ("quests.max", self.quests.max, self.quests.items.highest()),
+        ]
+    }
+
+    /// Returns the counters, in the same order as [`ChronologicalData::counters`].
+    fn counters_mut(&mut self) -> [&mut u16; 6] {
+        [
+            &mut self.character_classes.max,
+            &mut self.art_status_max,
+            &mut self.skill_status_max,
+            &mut self.unlocked_characters.max,
+            &mut self.npc_collectopedia.max,
+            &mut self.quests.max,
+        ]
+    }
+}
+
+impl<'a, const R: usize, const C: usize> CharacterChronoAccessor<'a, R, C> {
+    fn new(max: &'a mut u16, table: CharacterChrono<'a, R, C>, index: usize) -> Self {
+        assert!(index < C, "character index out of bounds");
+        Self { max, table, index }
+    }
+
+    /// Returns the order value for an entry, or [`None`] if the entry is absent.
+    ///
+    /// Higher values are more recent.
+    ///
+    /// ## Panics
+    /// Panics if the entry ID is outside the bounds of this table.
+    pub fn get(&self, id: usize) -> Option<u16> {
+        match &self.table {
+            CharacterChrono::Table(table) => {
+                Some(table.buf[self.index][id]).filter(|v| !v.is_null())
+            }
+            CharacterChrono::Hero(tables) => {
+                let id = hero_id(id);
+                tables[self.index]
+                    .iter()
+                    .find(|p| p.id == id)
+                    .map(|p| p.sort)
+            }
+        }
+    }
+
+    /// Checks whether an entry is registered in the order.
+    ///
+    /// ## Panics
+    /// Panics if the entry ID is outside the bounds of this table.
+    pub fn is_present(&self, id: usize) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Returns the IDs of all registered entries, most recent first.
+    pub fn entries(&self) -> Vec<usize> {
+        let mut entries: Vec<_> = match &self.table {
+            CharacterChrono::Table(table) => table.buf[self.index]
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(id, &v)| (id, v))
+                .collect(),
+            CharacterChrono::Hero(tables) => tables[self.index]
+                .iter()
+                .map(|p| (p.id.into(), p.sort))
+                .collect(),
+        };
+        entries.sort_by_key(|&(_, v)| NullsLastReverse(v));
+        entries.into_iter().map(|(id, _)| id).collect()
+    }
+
+    /// Removes an entry from the order.
+    ///
+    /// This does nothing if the entry is absent.
+    ///
+    /// ## Panics
+    /// Panics if the entry ID is outside the bounds of this table.
+    pub fn remove(&mut self, id: usize) {
+        let Some(value) = self.get(id) else {
+            return;
+        };
+        if value == *self.max {
+            // Prevent the max value from growing too large
+            *self.max -= 1;
+        }
+        match &mut self.table {
+            CharacterChrono::Table(table) => table.buf[self.index][id] = 0,
+            CharacterChrono::Hero(tables) => {
+                let table = &mut tables[self.index];
+                let id = hero_id(id);
+                let pos = table.iter().position(|p| p.id == id).unwrap();
+                table.remove(pos);
+            }
+        }
+    }
+
+    fn set(&mut self, id: usize, value: u16) {
+        match &mut self.table {
+            CharacterChrono::Table(table) => table.buf[self.index][id] = value,
+            CharacterChrono::Hero(tables) => {
+                let table = &mut tables[self.index];
+                let id = hero_id(id);
+                let new = IdSortPair { id, sort: value };
+                // Keep entries sorted by ID
+                let pos = table.iter().position(|p| p.id >= id);
+                let pos = pos.unwrap_or(table.len());
+                if table.get(pos).is_some_and(|p| p.id == id) {
+                    table.set(pos, new);
+                } else {
+                    table
+                        .try_insert(pos, new)
+                        .expect("hero chrono table is full");
+                }
+            }
+        }
+    }
+}
+
+impl<'a, const R: usize, const C: usize> ChronologicalOrder for CharacterChronoAccessor<'a, R, C> {
+    fn cmp_entries(&self, id_a: usize, id_b: usize) -> Ordering {
+        NullsLastReverse(self.get(id_a).unwrap_or_default())
+            .cmp(&NullsLastReverse(self.get(id_b).unwrap_or_default()))
+    }
+
+    fn swap(&mut self, id_a: usize, id_b: usize) {
+        let (Some(val_a), Some(val_b)) = (self.get(id_a), self.get(id_b)) else {
+            return;
+        };
+        self.set(id_a, val_b);
+        self.set(id_b, val_a);
+    }
+
+    /// Registers a new entry, or moves an existing entry to the start of the order.
+    ///
+    /// ## Panics
+    /// Panics if the entry ID is outside the bounds of this table, if the shared
+    /// counter reaches its maximum value, or if a hero's table is full.
+    fn insert(&mut self, id: usize) {
+        let val = self
+            .max
+            .checked_add(1)
+            .expect("chronological counter reached max value");
+        self.set(id, val);
+        *self.max = val;
+    }
+}
+
+/// Converts an entry ID for hero tables, which store IDs as `u16`.
+fn hero_id(id: usize) -> u16 {
+    id.try_into().expect("id out of bounds")
+}
+
+impl<const R: usize, const C: usize> TableInner<R, C> {
+    /// Returns the highest order value in the table.
+    fn highest(&self) -> u16 {
+        self.buf.iter().flatten().copied().max().unwrap_or_default()
+    }
+}
+
+impl<const R: usize> ChronologicalList<R> {
+    /// Returns the order value for an entry, or [`None`] if the entry is absent.
+    ///
+    /// Higher values are more recent.
+    ///
+    /// ## Panics
+    /// Panics if the entry ID is outside the bounds of this list.
+    pub fn get(&self, id: usize) -> Option<u16> {
+        Some(self.items.buf[0][id]).filter(|v| !v.is_null())
+    }
+
+    /// Returns whether the entry is present in the list.
+    ///
+    /// ## Panics
+    /// Panics if the entry ID is outside the bounds of this list.
+    pub fn is_present(&self, id: usize) -> bool {
+        self.get(id).is_some()
+    }
+}
+
 impl<const R: usize> ChronologicalOrder for ChronologicalList<R> {
     fn cmp_entries(&self, id_a: usize, id_b: usize) -> Ordering {
         NullsLastReverse(self.items.buf[0][id_a]).cmp(&NullsLastReverse(self.items.buf[0][id_b]))
@@ -145,51 +486,46 @@ impl<const R: usize> ChronologicalOrder for ChronologicalList<R> {
     }
 }
 
+/// Chronological order for inventory items, i.e. the in-game "Sort by Newest" order.
+///
+/// Entry IDs are item IDs. If multiple slots hold the same item, only the first one
+/// is considered.
 impl ChronologicalOrder for Inventory {
     fn cmp_entries(&self, id_a: usize, id_b: usize) -> Ordering {
         let id_a: u16 = id_a.try_into().unwrap();
         let id_b: u16 = id_b.try_into().unwrap();
-        let (ty_a, ty_b) = (
-            ItemType::get_by_item_id(id_a),
-            ItemType::get_by_item_id(id_b),
-        );
         let (slot_a, slot_b) = (
-            self.slots(ty_a)
-                .iter()
-                .find(|s| s.item_id() == id_a)
-                .expect("item a not found"),
-            self.slots(ty_b)
-                .iter()
-                .find(|s| s.item_id() == id_b)
-                .expect("item b not found"),
+            self.find_slot(id_a).map(ItemSlot::chronological_id),
+            self.find_slot(id_b).map(ItemSlot::chronological_id),
         );
-        NullsLastReverse(slot_a.chronological_id())
-            .cmp(&NullsLastReverse(slot_b.chronological_id()))
+        NullsLastReverse(slot_a.unwrap_or_default())
+            .cmp(&NullsLastReverse(slot_b.unwrap_or_default()))
     }
 
     fn swap(&mut self, id_a: usize, id_b: usize) {
         let id_a: u16 = id_a.try_into().unwrap();
         let id_b: u16 = id_b.try_into().unwrap();
 
-        let (slot_a, slot_b) = self.split_slots_mut(id_a, id_b);
+        let Some((slot_a, slot_b)) = self.split_slots_mut(id_a, id_b) else {
+            return;
+        };
 
         let b_chrono = slot_b.chronological_id();
         slot_b.set_chronological_id(slot_a.chronological_id());
         slot_a.set_chronological_id(b_chrono);
     }
 
+    /// Moves an item to the start of the order.
+    ///
+    /// ## Panics
+    /// Panics if the item is not in the inventory.
     fn insert(&mut self, id: usize) {
         let id: u16 = id.try_into().unwrap();
-        // Item table will also wrap on overflow.
-        let max = self.chronological_id_max.wrapping_add(1);
-        self.chronological_id_max = max;
+        let max = self.next_chronological_id();
         // Assume item has already been registered.
-        let slot = self
-            .slots_mut(ItemType::get_by_item_id(id))
-            .iter_mut()
-            .find(|s| s.item_id() == id)
-            .expect("item not yet registered");
+        let slot = self.find_slot_mut(id).expect("item not yet registered");
         slot.set_chronological_id(max);
+        self.chronological_id_max = max;
     }
 }
 
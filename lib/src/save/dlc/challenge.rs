@@ -40,7 +40,7 @@ pub struct ChallengeBattle {
 #[derive(SaveBin, Debug)]
 #[size(32)]
 pub struct Challenge {
-    ranks: [u32; CHALLENGE_BATTLE_DIFFICULTY_MAX],
+    ranks: [ChallengeRank; CHALLENGE_BATTLE_DIFFICULTY_MAX],
     best_time: [f32; CHALLENGE_BATTLE_DIFFICULTY_MAX],
     pub clear_count: u32,
     /// Purpose unclear
@@ -66,8 +66,9 @@ pub enum ChallengeDifficulty {
     Hard = 2,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(SaveBin, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(feature = "strum", derive(strum::EnumIter, strum::FromRepr))]
+#[repr(u32)]
 pub enum ChallengeRank {
     None = 0,
     S = 1,
@@ -76,9 +77,9 @@ pub enum ChallengeRank {
     C = 4,
 }
 
-#[derive(Error, Debug)]
-#[error("unknown rank {0}")]
-pub struct RankFromIntError(u32);
+/// Former error type for `TryFrom<u32>` conversions to [`ChallengeRank`].
+#[deprecated(note = "use `DiscriminantError` instead")]
+pub type RankFromIntError = crate::error::DiscriminantError;
 
 #[derive(Error, Debug)]
 #[error("unsupported difficulty: {0:?}")]
@@ -173,11 +174,11 @@ impl ChallengeBattle {
 
 impl Challenge {
     pub fn get_rank(&self, difficulty: ChallengeDifficulty) -> ChallengeRank {
-        self.ranks[difficulty as usize].try_into().unwrap()
+        self.ranks[difficulty as usize]
     }
 
     pub fn set_rank(&mut self, difficulty: ChallengeDifficulty, rank: ChallengeRank) {
-        self.ranks[difficulty as usize] = rank as u32;
+        self.ranks[difficulty as usize] = rank;
     }
 
     pub fn get_best_time(&self, difficulty: ChallengeDifficulty) -> f32 {
@@ -192,21 +193,6 @@ impl Challenge {
     }
 }
 
-impl TryFrom<u32> for ChallengeRank {
-    type Error = RankFromIntError;
-
-    fn try_from(value: u32) -> Result<Self, Self::Error> {
-        Ok(match value {
-            0 => Self::None,
-            1 => Self::S,
-            2 => Self::A,
-            3 => Self::B,
-            4 => Self::C,
-            n => return Err(RankFromIntError(n)),
-        })
-    }
-}
-
 impl TryFrom<Difficulty> for ChallengeDifficulty {
     type Error = FromDifficultyError;
 
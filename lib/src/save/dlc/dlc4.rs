@@ -91,6 +91,13 @@ impl Dlc4 {
     ) -> SlotMut<ClassAccessory> {
         SlotMut(&mut self.extra_inventory[chr_id.get() - 1].battle_manual[class_id.get() - 1])
     }
+
+    /// Returns an iterator over every battle manual slot, for all characters and classes.
+    pub(crate) fn battle_manuals_mut(&mut self) -> impl Iterator<Item = &mut ClassAccessory> {
+        self.extra_inventory
+            .iter_mut()
+            .flat_map(|inv| inv.battle_manual.iter_mut())
+    }
 }
 
 impl<'a> CommunityChrono<'a> {
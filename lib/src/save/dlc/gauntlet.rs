@@ -16,7 +16,7 @@ const GAUNTLET_STATE_WHIMSY_MAX: usize = 2;
 #[derive(SaveBin, Debug)]
 #[size(72)]
 pub struct Gauntlet {
-    ranks: [u32; CHALLENGE_BATTLE_DIFFICULTY_MAX],
+    ranks: [ChallengeRank; CHALLENGE_BATTLE_DIFFICULTY_MAX],
     stage_reached: [u32; CHALLENGE_BATTLE_DIFFICULTY_MAX],
     high_score: [u32; CHALLENGE_BATTLE_DIFFICULTY_MAX],
     time: [f32; CHALLENGE_BATTLE_DIFFICULTY_MAX],
@@ -47,11 +47,12 @@ pub struct GauntletState {
     #[loc(0x18)]
     pub gauntlet_id: u32,
     lead_character_id: u32,
-    challenge_difficulty: u32,
+    challenge_difficulty: Difficulty,
     /// Original game map time, used to reset after the run ends.
     pub game_map_time: MapTime<u32>,
     /// Original game weather, used to reset after the run ends.  
     /// ID for `RSC_WeatherSet`
+    #[table("RSC_WeatherSet")]
     pub game_weather: u32,
     /// Original game camera distance, used to reset after the run ends.
     pub game_camera_distance: f32,
@@ -79,16 +80,20 @@ pub struct GauntletState {
     /// Current map ID. (Actual map ID = value + 75, ID for `SYS_MapList`)
     pub map_id: u32,
     /// Set if a map jump needs to happen. ID for `BTL_ChSU_MapBattleLock`.
+    #[table("BTL_ChSU_MapBattleLock")]
     pub next_map_pos_id: u32,
     pub last_stage: u32,
     pub current_score: u32,
     /// 0-3
+    #[range(0, 3)]
     pub shuffle_tickets: u32,
     /// 100: one charge, 200: two charges, 300: three charges
     pub launch_charge: f32,
     /// 0-100
+    #[range(0, 100)]
     pub nopwatch_gauge: f32,
     /// 0-6
+    #[range(0, 6)]
     pub no_ko_streak: u32,
 
     /// Number of noponstones gained so far. Unclear why it is here, as it can be calculated
@@ -105,8 +110,10 @@ pub struct GauntletState {
     /// ID for `BTL_ChSU_SettingGate`. It's likely that setting both of these will
     /// open the whimsy screen when the save is loaded.
     // needs testing
+    #[table("BTL_ChSU_SettingGate")]
     pub whimsy: [u32; GAUNTLET_STATE_WHIMSY_MAX],
     /// 0-900
+    #[range(0, 900)]
     pub chain_gauge: f32,
     /// Unsure
     level_multiplier: f32,
@@ -140,11 +147,11 @@ pub struct DateTime {
 
 impl Gauntlet {
     pub fn get_rank(&self, difficulty: ChallengeDifficulty) -> ChallengeRank {
-        self.ranks[difficulty as usize].try_into().unwrap()
+        self.ranks[difficulty as usize]
     }
 
     pub fn set_rank(&mut self, difficulty: ChallengeDifficulty, rank: ChallengeRank) {
-        self.ranks[difficulty as usize] = rank as u32;
+        self.ranks[difficulty as usize] = rank;
     }
 
     pub fn get_best_time(&self, difficulty: ChallengeDifficulty) -> f32 {
@@ -182,14 +189,13 @@ impl Gauntlet {
 
 impl GauntletState {
     pub fn get_challenge_difficulty(&self) -> ChallengeDifficulty {
-        Difficulty::try_from(self.challenge_difficulty)
-            .expect("invalid difficulty")
+        self.challenge_difficulty
             .try_into()
             .expect("difficulty not supported")
     }
 
     pub fn set_challenge_difficulty(&mut self, difficulty: ChallengeDifficulty) {
-        self.challenge_difficulty = difficulty as u32;
+        self.challenge_difficulty = difficulty.into();
     }
 
     pub fn get_lead_character(&self) -> u32 {
@@ -1,6 +1,10 @@
 use recordkeeper_macros::SaveBin;
 
-use crate::{error::SaveError, item::ITEM_ACCESSORY_MAX, SaveResult};
+use crate::{
+    error::SaveError,
+    item::{Inventory, ITEM_ACCESSORY_MAX},
+    SaveResult,
+};
 
 /// Item ID to be used to mark accessories as crafted.
 pub const CRAFTED_ITEM_ID: u16 = 793;
@@ -78,10 +82,12 @@ impl AccessoryCrafting {
     /// ## Errors
     ///
     /// The function fails if an entry must be created, but the craft data inventory is full.
+    /// If the inventory should have room, [`AccessoryCrafting::compact`] may be used to reclaim
+    /// entries that are no longer in use.
     pub fn set_data(&mut self, item_slot: usize, data: CraftItemData) -> SaveResult<()> {
         let offset = self.offsets[item_slot];
 
-        if offset != u16::MAX {
+        if offset != u16::MAX && usize::from(offset) < MASHA_DATA_MAX {
             // Slot already initialized
             self.data[offset as usize] = data;
             return Ok(());
@@ -89,14 +95,7 @@ impl AccessoryCrafting {
 
         // Need to allocate a new data sector: first, find an
         // unused offset.
-        let mut offsets = self.offsets.clone();
-        offsets.sort_unstable();
-
-        let Some((_, empty)) = offsets
-            .into_iter()
-            .zip(0..MASHA_DATA_MAX)
-            .find(|(a, b)| *a as usize != *b)
-        else {
+        let Some(empty) = self.used_data().iter().position(|used| !used) else {
             return Err(SaveError::MashaInventoryFull);
         };
 
@@ -106,6 +105,125 @@ impl AccessoryCrafting {
 
         Ok(())
     }
+
+    /// Returns an iterator over the item slots that have crafted data, along with
+    /// their data.
+    ///
+    /// Item slots are yielded in ascending order, and are indices for
+    /// [`Inventory::accessories`].
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &CraftItemData)> {
+        self.offsets
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, &offset)| self.data.get(usize::from(offset)).map(|d| (slot, d)))
+    }
+
+    /// Returns the number of data entries that are referenced by at least one item slot.
+    ///
+    /// The craft inventory is full when this reaches the maximum of 300 entries.
+    pub fn used_count(&self) -> usize {
+        self.used_data().iter().filter(|used| **used).count()
+    }
+
+    /// Returns the indices of data entries that are not referenced by any item slot,
+    /// but contain non-empty data.
+    ///
+    /// Orphaned entries are left behind when crafted accessories are removed. They do
+    /// not prevent new entries from being allocated.
+    pub fn orphaned_data(&self) -> Vec<usize> {
+        let used = self.used_data();
+        (0..MASHA_DATA_MAX)
+            .filter(|&i| !used[i] && self.data[i] != CraftItemData::EMPTY)
+            .collect()
+    }
+
+    /// Returns the item slots that reference a data entry, but should not have one.
+    ///
+    /// This is the case when the slot doesn't hold a crafted accessory, when the
+    /// data index is out of bounds, or when the data entry is shared with a
+    /// previous slot.
+    pub fn dangling_offsets(&self, inventory: &Inventory) -> Vec<usize> {
+        let mut seen = [false; MASHA_DATA_MAX];
+        self.offsets
+            .iter()
+            .enumerate()
+            .filter(|(_, &offset)| offset != u16::MAX)
+            .filter(|&(slot, &offset)| {
+                let offset = usize::from(offset);
+                if offset >= MASHA_DATA_MAX
+                    || !inventory.accessories[slot].is_crafted_accessory()
+                    || seen[offset]
+                {
+                    return true;
+                }
+                seen[offset] = true;
+                false
+            })
+            .map(|(slot, _)| slot)
+            .collect()
+    }
+
+    /// Reclaims unused crafted data entries.
+    ///
+    /// Dangling offsets (see [`AccessoryCrafting::dangling_offsets`]) are removed,
+    /// then the data of the remaining crafted accessories is moved to the start of
+    /// the data table, in item slot order. Unused entries are cleared.
+    ///
+    /// Returns the number of entries that are free after compaction.
+    pub fn compact(&mut self, inventory: &Inventory) -> usize {
+        for slot in self.dangling_offsets(inventory) {
+            self.offsets[slot] = u16::MAX;
+        }
+
+        let mut data = Box::new([CraftItemData::EMPTY; MASHA_DATA_MAX]);
+        let mut next = 0;
+        for offset in self.offsets.iter_mut() {
+            if *offset == u16::MAX {
+                continue;
+            }
+            data[next] = self.data[usize::from(*offset)];
+            *offset = next.try_into().unwrap();
+            next += 1;
+        }
+        self.data = data;
+
+        MASHA_DATA_MAX - next
+    }
+
+    /// Returns which data entries are referenced by at least one item slot.
+    fn used_data(&self) -> [bool; MASHA_DATA_MAX] {
+        let mut used = [false; MASHA_DATA_MAX];
+        for &offset in self.offsets.iter() {
+            if let Some(used) = used.get_mut(usize::from(offset)) {
+                *used = true;
+            }
+        }
+        used
+    }
+
+    /// Moves crafted data references after the accessory inventory has been
+    /// rearranged. `slot_map` maps old slot indices to new ones.
+    ///
+    /// References from slots that have no new position are dropped.
+    pub(crate) fn remap_slots(&mut self, slot_map: &[Option<u16>]) {
+        let mut offsets = Box::new([u16::MAX; ITEM_ACCESSORY_MAX]);
+        for (old, new) in slot_map.iter().enumerate() {
+            if let Some(new) = new {
+                offsets[usize::from(*new)] = self.offsets[old];
+            }
+        }
+        self.offsets = offsets;
+    }
+}
+
+impl CraftItemData {
+    /// Data for unused entries.
+    const EMPTY: Self = Self {
+        stat_boosts: [StatBoost { stat: 0, amount: 0 }; MASHA_STAT_BOOSTS_MAX],
+        display_id: 0,
+        enhance_id: 0,
+        level: 0,
+    };
 }
 
 impl Default for CraftItemData {
@@ -23,6 +23,7 @@ pub struct PowAugment {
     learned: BitFlags<1, { (POW_AUGMENT_NUM_FLAGS + 31) / 32 }>,
     /// ID for `CHR_PC`
     #[loc(0xa)]
+    #[table("CHR_PC")]
     pub chr_id: u8,
     /// The number of unlocked growth tree tiers
     pub unlocked_tiers: u8,
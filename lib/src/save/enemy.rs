@@ -1,7 +1,6 @@
 use std::num::NonZeroUsize;
 
 use recordkeeper_macros::SaveBin;
-use thiserror::Error;
 
 pub const ENEMY_TOMBSTONE_MAX: usize = 200;
 pub const SOUL_HACK_ACHIEVEMENT_MAX: usize = 220;
@@ -39,7 +38,7 @@ pub enum Achievement {
     InProgress(u32),
 }
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(SaveBin, Clone, Copy, PartialEq, Debug)]
 #[cfg_attr(feature = "strum", derive(strum::EnumIter, strum::FromRepr))]
 #[repr(u32)]
 pub enum Difficulty {
@@ -49,9 +48,9 @@ pub enum Difficulty {
     VeryHard = 3,
 }
 
-#[derive(Error, Debug)]
-#[error("unknown difficulty ID {0}")]
-pub struct DifficultyFromIntError(u32);
+/// Former error type for `TryFrom<u32>` conversions to [`Difficulty`].
+#[deprecated(note = "use `DiscriminantError` instead")]
+pub type DifficultyFromIntError = crate::error::DiscriminantError;
 
 impl EnemyTombstone {
     pub fn time_record(&self, difficulty: Difficulty) -> &TombstoneTime {
@@ -111,27 +110,3 @@ impl From<Achievement> for u32 {
         }
     }
 }
-
-#[cfg(feature = "strum")]
-impl TryFrom<u32> for Difficulty {
-    type Error = DifficultyFromIntError;
-
-    fn try_from(value: u32) -> Result<Self, Self::Error> {
-        Self::from_repr(value).ok_or(DifficultyFromIntError(value))
-    }
-}
-
-#[cfg(not(feature = "strum"))]
-impl TryFrom<u32> for Difficulty {
-    type Error = DifficultyFromIntError;
-
-    fn try_from(value: u32) -> Result<Self, Self::Error> {
-        Ok(match value {
-            0 => Self::Normal,
-            1 => Self::Easy,
-            2 => Self::Hard,
-            3 => Self::VeryHard,
-            v => return Err(DifficultyFromIntError(v)),
-        })
-    }
-}
@@ -10,6 +10,7 @@ pub mod colony;
 #[derive(SaveBin, Debug)]
 pub struct FieldConfig {
     /// ID for `QST_List`
+    #[table("QST_List")]
     pub active_quest_id: u32,
 
     /// 0: Main, 2: Hero, 3: Side
@@ -43,7 +44,8 @@ pub struct ActiveMeal {
     pub time_max: f32,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(SaveBin, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[repr(u16)]
 pub enum MealType {
     Cooked = 1,
     Canteen = 2,
@@ -102,12 +104,8 @@ impl ActiveMeal {
     /// ## Panics
     /// Panics if the meal is not active, or the type is invalid.
     pub fn meal_type(&self) -> MealType {
-        match self.meal_type {
-            0 => panic!("no meal active"),
-            1 => MealType::Cooked,
-            2 => MealType::Canteen,
-            t => panic!("unknown meal type {t}"),
-        }
+        assert!(self.is_active(), "no meal active");
+        MealType::try_from(self.meal_type).unwrap()
     }
 
     pub fn set_meal_type(&mut self, meal_type: MealType) {
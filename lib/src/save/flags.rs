@@ -1,7 +1,10 @@
 use crate::error::SaveError;
 use crate::io::SaveBin;
-use recordkeeper_data_model::Model;
+use recordkeeper_data_model::{BitFlagsModel, FlagsModel, Model, TypeModel};
 use recordkeeper_macros::SaveBin;
+use std::any::TypeId;
+use std::fmt::Debug;
+use std::io::Cursor;
 use std::marker::PhantomData;
 
 const FLAG_1_BIT_COUNT: usize = 65536;
@@ -35,6 +38,7 @@ pub struct AllFlags {
 
 #[derive(SaveBin, Debug, Clone, Copy)]
 #[size(WORDS * 4)]
+#[manual_model]
 pub struct BitFlags<const BITS: usize, const WORDS: usize> {
     words: [u32; WORDS],
     _bits: [PhantomData<()>; BITS],
@@ -49,6 +53,58 @@ where
     flags: [B; N],
 }
 
+/// Named 1-bit flags, stored in `S`. Each flag is a variant of `F`.
+///
+/// ```ignore
+/// use recordkeeper::flags::{BitFlags, Flags};
+/// use recordkeeper_macros::Flag;
+///
+/// // Discriminants are bit indices
+/// #[derive(Flag, Clone, Copy, Debug, PartialEq)]
+/// pub enum ExampleFlag {
+///     First = 0,
+///     Third = 2,
+/// }
+///
+/// #[derive(SaveBin, Debug)]
+/// pub struct Example {
+///     pub flags: Flags<ExampleFlag, BitFlags<1, 1>>,
+/// }
+/// ```
+pub struct Flags<F, S> {
+    raw: S,
+    _flag: PhantomData<F>,
+}
+
+/// Types that name the bits of [`Flags`].
+///
+/// This is usually derived on fieldless enums, using `#[derive(Flag)]`.
+pub trait Flag: Copy + 'static {
+    /// All known flags
+    const ALL: &'static [Self];
+
+    /// Returns the index of the flag's bit.
+    fn index(self) -> usize;
+
+    /// Returns the name of the flag.
+    fn name(self) -> &'static str;
+}
+
+/// Types that can store 1-bit flags.
+pub trait FlagStorage {
+    /// Returns the value of the bit at the given index.
+    ///
+    /// ## Panics
+    /// Panics if the index is out of bounds.
+    fn get_bit(&self, index: usize) -> bool;
+
+    /// Sets the value of the bit at the given index.
+    ///
+    /// ## Panics
+    /// Panics if the index is out of bounds.
+    fn set_bit(&mut self, index: usize, value: bool);
+}
+
 impl FlagType {
     pub fn from_bits(bits: usize) -> Self {
         match bits {
@@ -165,6 +221,126 @@ where
     }
 }
 
+impl<F: Flag, S: FlagStorage> Flags<F, S> {
+    pub fn is_set(&self, flag: F) -> bool {
+        self.raw.get_bit(flag.index())
+    }
+
+    pub fn set(&mut self, flag: F, value: bool) {
+        self.raw.set_bit(flag.index(), value)
+    }
+
+    /// Returns an iterator over the flags that are set.
+    pub fn iter(&self) -> impl Iterator<Item = F> + '_ {
+        F::ALL.iter().copied().filter(|&f| self.is_set(f))
+    }
+}
+
+impl<const WORDS: usize> FlagStorage for BitFlags<1, WORDS> {
+    fn get_bit(&self, index: usize) -> bool {
+        self.get(index).expect("index out of bounds") != 0
+    }
+
+    fn set_bit(&mut self, index: usize, value: bool) {
+        self.set(index, value.into())
+    }
+}
+
+impl FlagStorage for u8 {
+    fn get_bit(&self, index: usize) -> bool {
+        assert!(index < 8, "index out of bounds");
+        *self & (1 << index) != 0
+    }
+
+    fn set_bit(&mut self, index: usize, value: bool) {
+        assert!(index < 8, "index out of bounds");
+        *self = (*self & !(1 << index)) | (u8::from(value) << index);
+    }
+}
+
+impl<const BITS: usize, const WORDS: usize> Model for BitFlags<BITS, WORDS> {
+    fn model() -> TypeModel {
+        TypeModel::BitFlags(Box::new(BitFlagsModel {
+            bits: BITS,
+            words: WORDS,
+        }))
+    }
+}
+
+impl<F, S> SaveBin for Flags<F, S>
+where
+    S: SaveBin,
+    SaveError: From<S::ReadError> + From<S::WriteError>,
+{
+    type ReadError = SaveError;
+    type WriteError = SaveError;
+
+    fn read(bytes: &mut Cursor<&[u8]>) -> Result<Self, Self::ReadError> {
+        Ok(Self {
+            raw: S::read(bytes)?,
+            _flag: PhantomData,
+        })
+    }
+
+    fn write(&self, bytes: &mut [u8]) -> Result<(), Self::WriteError> {
+        Ok(self.raw.write(bytes)?)
+    }
+
+    const SIZE: usize = S::SIZE;
+}
+
+impl<F: Flag, S: Model> Model for Flags<F, S> {
+    fn model() -> TypeModel {
+        let name = std::any::type_name::<F>();
+        TypeModel::Flags(Box::new(FlagsModel {
+            name: name.rsplit("::").next().unwrap_or(name),
+            type_id: TypeId::of::<Self>(),
+            storage: S::model(),
+            flags: F::ALL.iter().map(|f| (f.name(), f.index())).collect(),
+        }))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<F, S: crate::io::serde::SerdeBin> crate::io::serde::SerdeBin for Flags<F, S> {
+    fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        S::serialize(&self.raw, serializer)
+    }
+
+    fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self {
+            raw: S::deserialize(deserializer)?,
+            _flag: PhantomData,
+        })
+    }
+}
+
+impl<F: Flag + Debug, S: FlagStorage> Debug for Flags<F, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<F, S: Clone> Clone for Flags<F, S> {
+    fn clone(&self) -> Self {
+        Self {
+            raw: self.raw.clone(),
+            _flag: PhantomData,
+        }
+    }
+}
+
+impl<F, S: Copy> Copy for Flags<F, S> {}
+
+impl<F, S: Default> Default for Flags<F, S> {
+    fn default() -> Self {
+        Self {
+            raw: S::default(),
+            _flag: PhantomData,
+        }
+    }
+}
+
 impl<const BITS: usize, const WORDS: usize> Default for BitFlags<BITS, WORDS> {
     fn default() -> Self {
         Self {
@@ -0,0 +1,83 @@
+use crate::{chrono::NullsLastReverse, SaveData};
+
+use super::{ItemSlot, ItemType};
+
+/// Order of occupied slots after compaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotOrder {
+    /// Keep the current relative order of occupied slots.
+    Unchanged,
+    /// Sort slots by item ID, in ascending order.
+    ItemId,
+    /// Sort slots by chronological order, with the most recently obtained items first.
+    Newest,
+}
+
+impl SaveData {
+    /// Moves every occupied inventory slot of the given type to the start of the
+    /// inventory, sorting slots by the given order.
+    ///
+    /// Slot indices are rewritten, and so are all references to the moved slots:
+    /// * accessories equipped by each character's classes,
+    /// * accessories in saved party formations,
+    /// * battle manuals (Future Redeemed),
+    /// * crafted accessory data.
+    ///
+    /// References to empty or out-of-bounds slots are cleared.
+    ///
+    /// Returns the new index of each slot, indexed by old slot index. Empty
+    /// slots are mapped to [`None`].
+    ///
+    /// ## Panics
+    /// Panics if the item type does not have inventory slots.
+    pub fn compact_inventory(&mut self, item_type: ItemType, order: SlotOrder) -> Vec<Option<u16>> {
+        let slots = self.inventory.slots_mut(item_type);
+
+        let mut occupied = (0..slots.len())
+            .filter(|&i| slots[i].is_valid())
+            .collect::<Vec<_>>();
+        match order {
+            SlotOrder::Unchanged => {}
+            SlotOrder::ItemId => occupied.sort_by_key(|&i| slots[i].item_id),
+            SlotOrder::Newest => {
+                occupied.sort_by_key(|&i| NullsLastReverse(slots[i].chronological_id))
+            }
+        }
+
+        let mut slot_map = vec![None; slots.len()];
+        let old_slots = slots.to_vec();
+        slots.fill(ItemSlot::default());
+        for (new, old) in occupied.into_iter().enumerate() {
+            let new_index = u16::try_from(new).unwrap();
+            slots[new] = ItemSlot {
+                slot_index: new_index,
+                ..old_slots[old]
+            };
+            slot_map[old] = Some(new_index);
+        }
+
+        self.remap_item_slots(item_type, &slot_map);
+        slot_map
+    }
+
+    /// Updates every reference to inventory slots of the given type, after
+    /// the inventory has been rearranged.
+    pub(crate) fn remap_item_slots(&mut self, item_type: ItemType, slot_map: &[Option<u16>]) {
+        for character in self.characters.iter_mut() {
+            for class in character.classes_mut() {
+                class.remap_accessories(item_type, slot_map);
+            }
+        }
+        for formation in self.party_formations.iter_mut() {
+            for character in formation.characters.iter_mut() {
+                character.class.remap_accessories(item_type, slot_map);
+            }
+        }
+        for manual in self.dlc4.battle_manuals_mut() {
+            manual.remap_slot(item_type, slot_map);
+        }
+        if item_type == ItemType::Accessory {
+            self.accessory_crafting.remap_slots(slot_map);
+        }
+    }
+}
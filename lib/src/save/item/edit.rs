@@ -65,18 +65,7 @@ impl<'a> ItemEditor<'a> {
     ///
     /// If the item is a crafted accessory, its extra data will also be cleared.
     pub fn clear(&mut self) {
-        if self.slot.is_crafted_accessory() {
-            // Delete accessory crafting slot
-            self.crafting.remove_data(self.slot_id);
-            self.slot.flags &= !(SlotFlags::HasCraftData as u8);
-        }
-
-        let slot = &mut self.slot;
-        slot.item_id = 0;
-        slot.amount = 0;
-        slot.chronological_id = 0;
-        slot.item_type = 0;
-        slot.flags &= !(SlotFlags::Active as u8);
+        self.slot.clear(self.crafting, self.slot_id);
     }
 
     /// Returns a mutable view of the accessory crafting data for the item slot, if present.
@@ -1,17 +1,22 @@
-use std::num::NonZeroUsize;
+use std::{cmp::Ordering, io::Cursor, num::NonZeroUsize};
 
 use recordkeeper_macros::SaveBin;
-use thiserror::Error;
+
+use crate::io::SaveBin;
+use recordkeeper_data_model::{Model, TypeModel};
 
 use crate::{
     character::CHARACTER_MAX,
-    dlc::{CraftItemData, CRAFTED_ITEM_ID},
-    SaveData,
+    chrono::NullsLastReverse,
+    dlc::{AccessoryCrafting, CraftItemData, CRAFTED_ITEM_ID},
+    error::SaveError,
+    SaveData, SaveResult,
 };
 
 pub const ITEM_ACCESSORY_MAX: usize = 1500;
 pub const GEM_CATEGORY_MAX: usize = 20;
 
+pub mod compact;
 pub mod edit;
 
 #[derive(SaveBin, Debug)]
@@ -39,8 +44,9 @@ pub struct Inventory {
 
 /// An item slot in the player's inventory.
 ///
-/// To edit item slots, use the [`edit::ItemEditor`] struct.
-#[derive(SaveBin, Debug, Clone, Copy)]
+/// To edit item slots, use the [`edit::ItemEditor`] struct. To add or remove items
+/// without managing slots manually, use [`Inventory::add_item`] and [`Inventory::remove_item`].
+#[derive(SaveBin, Debug, Clone, Copy, Default)]
 #[size(16)]
 pub struct ItemSlot {
     item_id: u16,
@@ -52,6 +58,7 @@ pub struct ItemSlot {
     flags: u8,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SlotFlags {
     /// The slot has an item inside
     Active = 1,
@@ -96,14 +103,17 @@ pub struct LegacyForge {
 }
 
 /// See [`LegacyForge`]
-#[derive(SaveBin, Debug)]
-#[size(4)]
+#[derive(Debug)]
 pub struct LegacyForgeData {
     pub enhance_id: u16,
     pub valid: bool,
+    /// Leftover data may contain validity bytes other than 0 and 1. The raw
+    /// byte is written back unless `valid` was changed.
+    raw_valid: u8,
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
+#[derive(SaveBin, PartialEq, Eq, Clone, Copy, Debug)]
+#[repr(u32)]
 pub enum ItemType {
     Cylinder = 1,
     Gem = 2,
@@ -116,9 +126,9 @@ pub enum ItemType {
     Extra = 9,
 }
 
-#[derive(Error, Debug)]
-#[error("unknown item type {0}")]
-pub struct TypeFromIntError(u32);
+/// Former error type for `TryFrom<u32>` conversions to [`ItemType`].
+#[deprecated(note = "use `DiscriminantError` instead")]
+pub type TypeFromIntError = crate::error::DiscriminantError;
 
 impl Inventory {
     pub fn slots(&self, item_type: ItemType) -> &[ItemSlot] {
@@ -149,33 +159,180 @@ impl Inventory {
         }
     }
 
+    /// Returns the first occupied slot that holds the given item ID, if any.
+    pub fn find_slot(&self, item_id: u16) -> Option<&ItemSlot> {
+        let item_type = ItemType::get_by_item_id(item_id).filter(|t| t.has_slots())?;
+        self.slots(item_type)
+            .iter()
+            .find(|s| s.is_valid() && s.item_id() == item_id)
+    }
+
+    /// Returns a mutable view of the first occupied slot that holds the given item ID, if any.
+    pub fn find_slot_mut(&mut self, item_id: u16) -> Option<&mut ItemSlot> {
+        let item_type = ItemType::get_by_item_id(item_id).filter(|t| t.has_slots())?;
+        self.slots_mut(item_type)
+            .iter_mut()
+            .find(|s| s.is_valid() && s.item_id() == item_id)
+    }
+
+    /// Adds an amount of an item to the inventory.
+    ///
+    /// If the item is already in the inventory, the amount is added to the existing
+    /// stack (saturating at `u16::MAX`). Otherwise, the item is placed in the first
+    /// free slot for its type, and marked as new. In both cases, the item becomes the
+    /// most recent entry in the chronological order, like when it is obtained in-game.
+    ///
+    /// Crafted accessories ([`CRAFTED_ITEM_ID`]) never stack: each call allocates a new
+    /// slot, and initializes its crafting data with default values. Their amount must
+    /// be 1.
+    ///
+    /// Returns the index of the slot that holds the item.
+    ///
+    /// ## Errors
+    /// The function fails if the item ID is unknown, if the amount is 0 (or more than 1
+    /// for crafted accessories), if there are no free slots for the item's type, or if
+    /// crafted data initialization fails.
+    pub fn add_item(
+        &mut self,
+        crafting: &mut AccessoryCrafting,
+        item_id: u16,
+        amount: u16,
+    ) -> SaveResult<usize> {
+        let item_type = ItemType::get_by_item_id(item_id)
+            .filter(|t| t.has_slots())
+            .ok_or(SaveError::UnknownItem(item_id))?;
+        let is_crafted = item_type == ItemType::Accessory && item_id == CRAFTED_ITEM_ID;
+        if amount == 0 || (is_crafted && amount > 1) {
+            return Err(SaveError::InvalidItemAmount { item_id, amount });
+        }
+        let chronological_id = self.next_chronological_id();
+        let slots = self.slots_mut(item_type);
+
+        if !is_crafted {
+            if let Some((index, slot)) = slots
+                .iter_mut()
+                .enumerate()
+                .find(|(_, s)| s.is_valid() && s.item_id == item_id)
+            {
+                slot.amount = slot.amount.saturating_add(amount);
+                slot.chronological_id = chronological_id;
+                self.chronological_id_max = chronological_id;
+                return Ok(index);
+            }
+        }
+
+        let (index, slot) = slots
+            .iter_mut()
+            .enumerate()
+            .find(|(_, s)| !s.is_valid())
+            .ok_or(SaveError::InventoryFull(item_id))?;
+        if is_crafted {
+            crafting.set_data(index, CraftItemData::default())?;
+        }
+        *slot = ItemSlot {
+            item_id,
+            slot_index: index.try_into().unwrap(),
+            item_type: item_type as u32,
+            chronological_id,
+            amount,
+            flags: SlotFlags::Active as u8 | SlotFlags::New as u8,
+        };
+        if is_crafted {
+            slot.flags |= SlotFlags::HasCraftData as u8;
+        }
+        self.chronological_id_max = chronological_id;
+        Ok(index)
+    }
+
+    /// Removes an amount of an item from the inventory.
+    ///
+    /// If the item is spread across multiple slots (e.g. crafted accessories), slots
+    /// are emptied in order until the requested amount has been removed. Slots that
+    /// reach an amount of 0 are cleared, along with their crafting data.
+    ///
+    /// Returns the amount that was actually removed, which is less than `amount` if
+    /// the inventory did not hold enough items.
+    pub fn remove_item(
+        &mut self,
+        crafting: &mut AccessoryCrafting,
+        item_id: u16,
+        amount: u16,
+    ) -> u16 {
+        let Some(item_type) = ItemType::get_by_item_id(item_id).filter(|t| t.has_slots()) else {
+            return 0;
+        };
+        let mut left = amount;
+        for (index, slot) in self.slots_mut(item_type).iter_mut().enumerate() {
+            if left == 0 {
+                break;
+            }
+            if !slot.is_valid() || slot.item_id != item_id {
+                continue;
+            }
+            let removed = slot.amount.min(left);
+            slot.amount -= removed;
+            left -= removed;
+            if slot.amount == 0 {
+                slot.clear(crafting, index);
+            }
+        }
+        amount - left
+    }
+
+    /// Returns the occupied slots for the given item type, following the in-game
+    /// "Sort by Newest" order (most recently obtained items first).
+    ///
+    /// ## Panics
+    /// Panics if the item type does not have inventory slots.
+    pub fn slots_chronological(&self, item_type: ItemType) -> Vec<&ItemSlot> {
+        let mut slots = self
+            .slots(item_type)
+            .iter()
+            .filter(|s| s.is_valid())
+            .collect::<Vec<_>>();
+        slots.sort_by_key(|s| NullsLastReverse(s.chronological_id()));
+        slots
+    }
+
+    /// Returns the chronological ID for the next obtained item. The current maximum
+    /// is not updated.
+    pub(crate) fn next_chronological_id(&self) -> u32 {
+        // Item table will also wrap on overflow.
+        self.chronological_id_max.wrapping_add(1)
+    }
+
+    /// Returns mutable references to the slots for the two item IDs.
+    ///
+    /// Returns [`None`] if either item is absent, or if both IDs point to the same slot.
     pub(crate) fn split_slots_mut(
         &mut self,
         id_a: u16,
         id_b: u16,
-    ) -> (&mut ItemSlot, &mut ItemSlot) {
+    ) -> Option<(&mut ItemSlot, &mut ItemSlot)> {
         let (ty_a, ty_b) = (
-            ItemType::get_by_item_id(id_a),
-            ItemType::get_by_item_id(id_b),
+            ItemType::get_by_item_id(id_a).filter(|t| t.has_slots())?,
+            ItemType::get_by_item_id(id_b).filter(|t| t.has_slots())?,
         );
         let (idx_a, idx_b) = (
             self.slots(ty_a)
                 .iter()
-                .position(|s| s.item_id() == id_a)
-                .expect("item a not found"),
+                .position(|s| s.is_valid() && s.item_id() == id_a)?,
             self.slots(ty_b)
                 .iter()
-                .position(|s| s.item_id() == id_b)
-                .expect("item b not found"),
+                .position(|s| s.is_valid() && s.item_id() == id_b)?,
         );
         if ty_a == ty_b {
             let slots = self.slots_mut(ty_a);
-            if idx_a > idx_b {
-                let (before_a, after_a) = slots.split_at_mut(idx_a);
-                (&mut after_a[0], &mut before_a[idx_b])
-            } else {
-                let (before_b, after_b) = slots.split_at_mut(idx_b);
-                (&mut before_b[idx_a], &mut after_b[0])
+            match idx_a.cmp(&idx_b) {
+                Ordering::Greater => {
+                    let (before_a, after_a) = slots.split_at_mut(idx_a);
+                    Some((&mut after_a[0], &mut before_a[idx_b]))
+                }
+                Ordering::Less => {
+                    let (before_b, after_b) = slots.split_at_mut(idx_b);
+                    Some((&mut before_b[idx_a], &mut after_b[0]))
+                }
+                Ordering::Equal => None,
             }
         } else {
             let (slots_a, slots_b) = (
@@ -184,7 +341,7 @@ impl Inventory {
             );
             // SAFETY: the two slot arrays are different, so we are just splitting
             // the borrow. The indexing is still performed safely with bound checks.
-            unsafe { (&mut (&mut *slots_a)[idx_a], &mut (&mut *slots_b)[idx_b]) }
+            unsafe { Some((&mut (&mut *slots_a)[idx_a], &mut (&mut *slots_b)[idx_b])) }
         }
     }
 }
@@ -219,6 +376,23 @@ impl ItemSlot {
         self.flags & (SlotFlags::Active as u8) != 0
     }
 
+    /// Returns whether the given flag is set for the slot.
+    pub fn is_flag_set(&self, flag: SlotFlags) -> bool {
+        self.flags & (flag as u8) != 0
+    }
+
+    pub(crate) fn set_flag(&mut self, flag: SlotFlags, value: bool) {
+        if value {
+            self.flags |= flag as u8;
+        } else {
+            self.flags &= !(flag as u8);
+        }
+    }
+
+    pub(crate) fn set_index(&mut self, index: u16) {
+        self.slot_index = index;
+    }
+
     /// Returns whether the slot hosts a crafted accessory. (DLC3)
     pub fn is_crafted_accessory(&self) -> bool {
         self.is_valid()
@@ -227,10 +401,31 @@ impl ItemSlot {
     }
 
     /// Returns the accessory crafting data for the item slot, if present.
+    ///
+    /// Crafting data is keyed by the slot's position in its inventory array. This uses
+    /// the slot's [`index`](ItemSlot::index), which matches its position in consistent
+    /// saves (see [`SaveData::check`]).
     pub fn craft_data<'s>(&self, save: &'s SaveData) -> Option<&'s CraftItemData> {
         save.accessory_crafting.get_data(self.slot_index as usize)
     }
 
+    /// Clears the slot. `index` is the slot's position in its inventory array.
+    ///
+    /// If the item is a crafted accessory, its extra data will also be cleared.
+    pub(crate) fn clear(&mut self, crafting: &mut AccessoryCrafting, index: usize) {
+        if self.is_crafted_accessory() {
+            // Delete accessory crafting slot
+            crafting.remove_data(index);
+            self.flags &= !(SlotFlags::HasCraftData as u8);
+        }
+
+        self.item_id = 0;
+        self.amount = 0;
+        self.chronological_id = 0;
+        self.item_type = 0;
+        self.flags &= !(SlotFlags::Active as u8);
+    }
+
     pub(crate) fn chronological_id(&self) -> u32 {
         self.chronological_id
     }
@@ -241,9 +436,30 @@ impl ItemSlot {
 }
 
 impl ItemType {
-    pub fn get_by_item_id(item_id: u16) -> Self {
-        //match item_id {}
-        todo!()
+    /// Returns the item type for the given item ID.
+    ///
+    /// Each `ITM_*` table uses its own ID range, so the type can be inferred
+    /// from the ID alone. Returns [`None`] if the ID is outside every known range.
+    pub fn get_by_item_id(item_id: u16) -> Option<Self> {
+        Some(match item_id {
+            1..=2000 => Self::Accessory,
+            2001..=4000 => Self::Collection,
+            4001..=6000 => Self::Collectopedia,
+            6001..=10000 => Self::Cylinder,
+            10001..=12000 => Self::Exchange,
+            12001..=14000 => Self::Gem,
+            14001..=16000 => Self::Info,
+            16001..=18000 => Self::Precious,
+            18001..=20000 => Self::Extra,
+            _ => return None,
+        })
+    }
+
+    /// Returns whether items of this type are stored in the [`Inventory`].
+    ///
+    /// Collectopedia entries are item IDs, but they are not stored in inventory slots.
+    pub fn has_slots(self) -> bool {
+        self != Self::Collectopedia
     }
 
     pub fn lang_id(self) -> &'static str {
@@ -282,21 +498,70 @@ impl GemLevels {
     }
 }
 
-impl TryFrom<u32> for ItemType {
-    type Error = TypeFromIntError;
-
-    fn try_from(value: u32) -> Result<Self, Self::Error> {
-        Ok(match value {
-            1 => Self::Cylinder,
-            2 => Self::Gem,
-            3 => Self::Collection,
-            4 => Self::Info,
-            5 => Self::Accessory,
-            6 => Self::Collectopedia,
-            7 => Self::Precious,
-            8 => Self::Exchange,
-            9 => Self::Extra,
-            i => return Err(TypeFromIntError(i)),
+impl SaveBin for LegacyForgeData {
+    type ReadError = SaveError;
+    type WriteError = SaveError;
+
+    fn read(bytes: &mut Cursor<&[u8]>) -> Result<Self, Self::ReadError> {
+        let raw = raw::LegacyForgeData::read(bytes)?;
+        Ok(Self {
+            enhance_id: raw.enhance_id,
+            valid: raw.valid != 0,
+            raw_valid: raw.valid,
+        })
+    }
+
+    fn write(&self, bytes: &mut [u8]) -> Result<(), Self::WriteError> {
+        self.to_raw().write(bytes)
+    }
+
+    const SIZE: usize = raw::LegacyForgeData::SIZE;
+}
+
+impl Model for LegacyForgeData {
+    fn model() -> TypeModel {
+        raw::LegacyForgeData::model()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl crate::io::serde::SerdeBin for LegacyForgeData {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_raw().serialize(serializer)
+    }
+
+    fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = raw::LegacyForgeData::deserialize(deserializer)?;
+        Ok(Self {
+            enhance_id: raw.enhance_id,
+            valid: raw.valid != 0,
+            raw_valid: raw.valid,
         })
     }
 }
+
+impl LegacyForgeData {
+    fn to_raw(&self) -> raw::LegacyForgeData {
+        let valid = if self.valid == (self.raw_valid != 0) {
+            self.raw_valid
+        } else {
+            self.valid.into()
+        };
+        raw::LegacyForgeData {
+            enhance_id: self.enhance_id,
+            valid,
+        }
+    }
+}
+
+mod raw {
+    use recordkeeper_macros::SaveBin;
+
+    /// Binary layout of [`super::LegacyForgeData`]
+    #[derive(SaveBin)]
+    #[size(4)]
+    pub(super) struct LegacyForgeData {
+        pub enhance_id: u16,
+        pub valid: u8,
+    }
+}
@@ -13,21 +13,22 @@ use crate::{
 
 use crate::menu::MenuData;
 use crate::util::FixVec;
-use recordkeeper_data_model::Model;
-use recordkeeper_macros::SaveBin;
+use recordkeeper_data_model::{DynamicValue, Model, PrimitiveValue};
+use recordkeeper_macros::{Flag, SaveBin};
 
 use self::character::CharacterSets;
 use self::chrono::ChronologicalData;
 use self::enemy::SoulHackAchievements;
 use self::field::colony::ColonyFlameClocks;
 use self::field::{ActiveMeal, CameraSettings, CollectionCache, FieldConfig};
-use self::flags::BitFlags;
+use self::flags::{BitFlags, Flags};
 use self::item::{GemLevels, LegacyForge};
 use self::npc::talk::NpcTalkFlags;
 use self::stats::PlayReportStats;
 use dlc::{AccessoryCrafting, ChallengeBattle, Dlc4, PowAugment, POW_AUGMENT_NUM};
 
 pub mod character;
+pub mod check;
 pub mod chrono;
 pub mod dlc;
 pub mod enemy;
@@ -40,6 +41,8 @@ pub mod stats;
 
 pub(crate) const SAVE_VERSION: u8 = 10;
 pub(crate) const SAVE_MAGIC: [u8; 4] = [0x6a, 0xfa, 0x68, 0xb3];
+/// Size of the save file. Bytes after the last known field are unused.
+pub(crate) const SAVE_SIZE: usize = 0x1c7000;
 
 /// Defines the save file binary structure.
 ///
@@ -49,6 +52,7 @@ pub(crate) const SAVE_MAGIC: [u8; 4] = [0x6a, 0xfa, 0x68, 0xb3];
 ///
 /// [`SaveFile::from_bytes`]: crate::SaveFile::from_bytes
 #[derive(SaveBin, Debug)]
+#[size(SAVE_SIZE)]
 pub struct SaveData {
     #[assert(SAVE_MAGIC)]
     _magic: [u8; 4],
@@ -61,9 +65,11 @@ pub struct SaveData {
     pub timestamp: SaveTimestamp,
     pub gold: u32,
     /// 0-100: at 100 a new cylinder is generated when harvesting ether.
+    #[range(0, 100)]
     pub ether_cylinder_progress: u16,
 
     /// ID for `SYS_MapJumpList`
+    #[table("SYS_MapJumpList")]
     pub respawn_point: u16,
     #[loc(0x30)]
     pub respawn_point_pos: Pos,
@@ -89,6 +95,7 @@ pub struct SaveData {
     /// For Ino's DX cylinders. (DLC2)
     ///
     /// 0-100: at 100 a new cylinder is generated when harvesting ether.
+    #[range(0, 100)]
     pub ether_cylinder_dx_progress: u16,
 
     /// Max chronological ID for Future Redeemed NPC community entries.
@@ -102,7 +109,7 @@ pub struct SaveData {
     pub npc_talk: NpcTalkFlags,
 
     #[loc(0x664)]
-    save_flags: BitFlags<1, 1>,
+    pub save_flags: Flags<SaveFlag, BitFlags<1, 1>>,
 
     /// Saved event flow ID for end-of-chapter saves
     #[loc(0x684)]
@@ -114,6 +121,7 @@ pub struct SaveData {
 
     /// ID for `RSC_WeatherSet`. The game only considers this if
     /// [`SaveFlag::WeatherLocked`] is set.
+    #[table("RSC_WeatherSet")]
     pub weather: u16,
 
     #[loc(0x6a0)]
@@ -135,6 +143,7 @@ pub struct SaveData {
     pub party_characters: FixVec<u16, PARTY_MAX>,
     /// Guest IDs from `FLD_NpcList`
     #[loc(0xe358)]
+    #[table("FLD_NpcList")]
     pub party_guests: FixVec<u16, PARTY_GUEST_MAX>,
 
     #[loc(0xe370)]
@@ -147,6 +156,7 @@ pub struct SaveData {
 
     /// ID for `BTL_Pair`
     #[loc(0x53c38)]
+    #[table("BTL_Pair")]
     dlc4_pairs: [u8; 3],
 
     #[loc(0x53c78)]
@@ -229,7 +239,7 @@ where
     pub minute: N,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Flag, Clone, Copy, Debug, PartialEq)]
 pub enum SaveFlag {
     TimeLocked = 0,
     WeatherLocked = 1,
@@ -244,14 +254,11 @@ pub enum SaveFlag {
 
 impl SaveData {
     pub fn is_flag_set(&self, flag: SaveFlag) -> bool {
-        self.save_flags
-            .get(flag as usize)
-            .map(|v| v != 0)
-            .expect("invalid flag index")
+        self.save_flags.is_set(flag)
     }
 
     pub fn set_flag(&mut self, flag: SaveFlag, value: bool) {
-        self.save_flags.set(flag as usize, u8::from(value).into())
+        self.save_flags.set(flag, value)
     }
 
     /// Returns whether the save file is a Future Redeemed file.
@@ -263,6 +270,22 @@ impl SaveData {
     pub fn has_gauntlet_save(&self) -> bool {
         self.is_flag_set(SaveFlag::Gauntlet) && self.challenge_battle.gauntlet_save().active
     }
+
+    /// Fields that must be set in a new save file, for it to be parsed correctly.
+    /// All other bytes are zero.
+    pub(crate) fn required_fields() -> Vec<(&'static str, DynamicValue)> {
+        vec![
+            ("_magic", DynamicValue::from_bytes(&SAVE_MAGIC)),
+            (
+                "save_version",
+                DynamicValue::Primitive(PrimitiveValue::U8(SAVE_VERSION)),
+            ),
+            (
+                "challenge_battle._unk",
+                DynamicValue::Primitive(PrimitiveValue::U32(6)),
+            ),
+        ]
+    }
 }
 
 impl PlayTime {
@@ -1,20 +1,27 @@
-use recordkeeper_macros::SaveBin;
+use recordkeeper_data_model::{DynamicValue, PrimitiveValue};
+use recordkeeper_macros::{Flag, SaveBin};
 
-use crate::{error::SaveError, flags::BitFlags};
+use crate::{
+    error::SaveError,
+    flags::{BitFlags, Flags},
+};
 
 pub(crate) const SYSTEM_VERSION: u32 = 2;
 pub(crate) const SYSTEM_MAGIC: [u8; 4] = [0x74, 0x60, 0xab, 0xe6];
+/// Size of the system file. Bytes after the last known field are unused.
+pub(crate) const SYSTEM_SIZE: usize = 0x8a0;
 
 pub const SAVE_SLOT_COUNT: usize = 5;
 
 #[derive(SaveBin, Debug)]
+#[size(SYSTEM_SIZE)]
 pub struct SystemData {
     #[assert(SYSTEM_MAGIC)]
     _magic: [u8; 4],
     #[assert(SYSTEM_VERSION, SaveError::UnsupportedVersion(ACTUAL, SYSTEM_VERSION))]
     version: u32,
 
-    flags: BitFlags<1, 2>,
+    pub flags: Flags<SystemFlag, BitFlags<1, 2>>,
 
     /// Index is `option_id` from `MNU_option_*`
     #[loc(0x10)]
@@ -44,6 +51,7 @@ pub struct SystemData {
     pub settings_save_counter: u64,
 }
 
+#[derive(Flag, Clone, Copy, Debug, PartialEq)]
 pub enum SystemFlag {
     /// Whether the main game has been cleared on any file.
     MainGameClear = 0,
@@ -67,11 +75,11 @@ pub enum SystemFlag {
 
 impl SystemData {
     pub fn is_flag_set(&self, flag: SystemFlag) -> bool {
-        self.flags.get(flag as usize).unwrap() != 0
+        self.flags.is_set(flag)
     }
 
     pub fn set_flag(&mut self, flag: SystemFlag, value: bool) {
-        self.flags.set(flag as usize, u8::from(value).into())
+        self.flags.set(flag, value)
     }
 
     pub fn get_setting(&self, option_id: usize) -> u16 {
@@ -81,4 +89,16 @@ impl SystemData {
     pub fn set_setting(&mut self, option_id: usize, value: u16) {
         self.global_settings[option_id] = value;
     }
+
+    /// Fields that must be set in a new system file, for it to be parsed correctly.
+    /// All other bytes are zero.
+    pub(crate) fn required_fields() -> Vec<(&'static str, DynamicValue)> {
+        vec![
+            ("_magic", DynamicValue::from_bytes(&SYSTEM_MAGIC)),
+            (
+                "version",
+                DynamicValue::Primitive(PrimitiveValue::U32(SYSTEM_VERSION)),
+            ),
+        ]
+    }
 }
@@ -1,4 +1,4 @@
-use recordkeeper_data_model::Model;
+use recordkeeper_data_model::{Model, StringModel, TypeModel, VecModel};
 use recordkeeper_macros::SaveBin;
 use thiserror::Error;
 
@@ -13,12 +13,14 @@ pub struct CapacityError;
 ///
 /// Extra bytes are not guaranteed to be nulls.
 #[derive(SaveBin, Debug)]
+#[manual_model]
 pub struct FixNullStr<const MAX: usize> {
     buf: [u8; MAX],
 }
 
 /// Bounded string with fixed storage and maximum length.
 #[derive(SaveBin, Debug)]
+#[manual_model]
 pub struct FixStr<const MAX: usize> {
     buf: [u8; MAX],
     len: u32,
@@ -26,6 +28,7 @@ pub struct FixStr<const MAX: usize> {
 
 /// Dynamic array with fixed capacity.
 #[derive(SaveBin, Debug, Clone)]
+#[manual_model]
 pub struct FixVec<T, const MAX: usize>
 where
     T: SaveBin + Model,
@@ -64,6 +67,19 @@ where
         Ok(())
     }
 
+    /// Inserts an element at the given index, shifting every element after it
+    /// to the right.
+    ///
+    /// ## Panics
+    /// Panics if the index is greater than the vector's length.
+    pub fn try_insert(&mut self, i: usize, to_add: T) -> Result<(), CapacityError> {
+        let len = self.len();
+        assert!(i <= len, "index out of bounds");
+        self.try_push(to_add)?;
+        self.buf[i..=len].rotate_right(1);
+        Ok(())
+    }
+
     pub fn len(&self) -> usize {
         // len <= MAX
         self.len as usize
@@ -101,4 +117,64 @@ where
         self.len -= 1;
         Ok(res)
     }
+
+    /// Removes the element at the given index, shifting every element after it
+    /// to the left.
+    ///
+    /// ## Panics
+    /// Panics if the index is out of bounds.
+    pub fn remove(&mut self, i: usize) -> T {
+        let len = self.len();
+        assert!(i < len, "index out of bounds");
+        self.buf[i..len].rotate_left(1);
+        self.try_pop().unwrap()
+    }
+}
+
+impl<T, const MAX: usize> Default for FixVec<T, MAX>
+where
+    T: Default + SaveBin + Model,
+    SaveError: From<<T as SaveBin>::ReadError>,
+    SaveError: From<<T as SaveBin>::WriteError>,
+{
+    /// Creates an empty vector.
+    fn default() -> Self {
+        Self {
+            buf: Box::new(std::array::from_fn(|_| T::default())),
+            len: 0,
+        }
+    }
+}
+
+impl<const MAX: usize> Model for FixNullStr<MAX> {
+    fn model() -> TypeModel {
+        TypeModel::String(Box::new(StringModel {
+            capacity: MAX,
+            len_type: None,
+        }))
+    }
+}
+
+impl<const MAX: usize> Model for FixStr<MAX> {
+    fn model() -> TypeModel {
+        TypeModel::String(Box::new(StringModel {
+            capacity: MAX,
+            len_type: Some("u32"),
+        }))
+    }
+}
+
+impl<T, const MAX: usize> Model for FixVec<T, MAX>
+where
+    T: SaveBin + Model,
+    SaveError: From<<T as SaveBin>::ReadError>,
+    SaveError: From<<T as SaveBin>::WriteError>,
+{
+    fn model() -> TypeModel {
+        TypeModel::Vec(Box::new(VecModel {
+            item: T::model(),
+            capacity: MAX,
+            len_type: "u64",
+        }))
+    }
 }
@@ -0,0 +1,93 @@
+use std::num::NonZeroU32;
+
+use recordkeeper::{
+    check::Problem,
+    dlc::CRAFTED_ITEM_ID,
+    item::{edit::ItemEditor, ItemType},
+    SaveFile,
+};
+use recordkeeper_data_model::PrimitiveValue;
+
+static SRC: &[u8] = include_bytes!("res/save-ch5-v10.sav");
+
+#[test]
+pub fn clean_save() {
+    let save = SaveFile::from_bytes(SRC).unwrap();
+    assert!(save.save().check().is_empty());
+}
+
+#[test]
+pub fn repair_broken_save() {
+    let mut save = SaveFile::from_bytes(SRC).unwrap();
+    let save = save.save_mut();
+
+    let class_id = NonZeroU32::new(u32::from(save.characters[0].selected_class)).unwrap();
+    let accessory = save.characters[0]
+        .class_data(class_id)
+        .accessory_slot(0)
+        .get()
+        .unwrap();
+    ItemEditor::new(save, ItemType::Accessory, accessory.slot_index().into()).clear();
+
+    save.party_characters.try_push(60).unwrap();
+    save.controlled_character_idx = 20;
+
+    let problems = save
+        .check()
+        .into_iter()
+        .map(|f| f.problem)
+        .collect::<Vec<_>>();
+    assert!(problems.contains(&Problem::AccessorySlotEmpty(accessory.slot_index())));
+    assert!(problems.contains(&Problem::UnselectableCharacter(60)));
+    assert!(problems
+        .iter()
+        .any(|p| matches!(p, Problem::ControlledCharacterOutOfBounds { index: 20, .. })));
+
+    assert!(!save.repair().is_empty());
+    assert!(save.check().is_empty());
+    assert!(save.party_characters.iter().all(|&c| c != 60));
+    assert!(save.characters[0]
+        .class_data(class_id)
+        .accessory_slot(0)
+        .get()
+        .is_none());
+}
+
+#[test]
+pub fn repair_slot_index_mismatch() {
+    let mut file = SaveFile::from_bytes(SRC).unwrap();
+    let gems = file.save().inventory.slots(ItemType::Gem);
+    assert!(gems[3].is_valid() && gems[5].is_valid());
+
+    file.set_path("inventory.gems[3].slot_index", PrimitiveValue::U16(5))
+        .unwrap();
+    let save = file.save_mut();
+    let findings = save.check();
+    assert_eq!(1, findings.len());
+    assert_eq!("inventory.gems[3]", findings[0].path);
+    assert_eq!(Problem::SlotIndexMismatch(5), findings[0].problem);
+
+    save.repair();
+    assert!(save.check().is_empty());
+    assert_eq!(3, save.inventory.slots(ItemType::Gem)[3].index());
+}
+
+#[test]
+pub fn repair_craft_data_by_position() {
+    let mut file = SaveFile::from_bytes(SRC).unwrap();
+    let save = file.save_mut();
+    let pos = save
+        .inventory
+        .add_item(&mut save.accessory_crafting, CRAFTED_ITEM_ID, 1)
+        .unwrap();
+    save.accessory_crafting.remove_data(pos);
+
+    let findings = save.check();
+    assert_eq!(1, findings.len());
+    assert_eq!(format!("inventory.accessories[{pos}]"), findings[0].path);
+    assert_eq!(Problem::MissingCraftData, findings[0].problem);
+
+    save.repair();
+    assert!(save.check().is_empty());
+    assert!(save.accessory_crafting.get_data(pos).is_some());
+}
@@ -0,0 +1,117 @@
+use std::{cmp::Ordering, num::NonZeroU32};
+
+use recordkeeper::{
+    character::class::{ClassEditor, ClassFlags},
+    chrono::ChronologicalOrder,
+    SaveData, SaveFile, SaveFlag,
+};
+use recordkeeper_data_model::{DynamicValue, Model, PrimitiveValue};
+
+static SRC: &[u8] = include_bytes!("res/save-ch5-v10.sav");
+
+#[test]
+pub fn hero_arts_sorted() {
+    let mut save = SaveFile::from_bytes(SRC).unwrap();
+    let chrono = &mut save.save_mut().chronological_data;
+
+    let mut arts = chrono.hero_arts(15);
+    assert_eq!(Some(139), arts.get(353));
+    assert_eq!(Some(143), arts.get(355));
+    assert!(!arts.is_present(360));
+
+    // New entries are inserted in ID order, and appear first
+    arts.insert(360);
+    assert_eq!(Some(1078), arts.get(360));
+    assert_eq!(360, arts.entries()[0]);
+    assert_eq!(Ordering::Less, arts.cmp_entries(360, 353));
+
+    arts.swap(360, 353);
+    assert_eq!(Some(139), arts.get(360));
+    assert_eq!(353, arts.entries()[0]);
+
+    // The counter is shared with other characters
+    let mut character_arts = chrono.character_arts(0);
+    character_arts.insert(1);
+    assert_eq!(Some(1079), character_arts.get(1));
+    character_arts.remove(1);
+    assert!(!character_arts.is_present(1));
+
+    let bytes = save.to_bytes().unwrap();
+    let (offset, model) = SaveData::model()
+        .find_path("chronological_data.hero_art_status[15]")
+        .map(|(offset, model)| (offset, model.decode(&bytes[offset..]).unwrap()))
+        .unwrap();
+    let DynamicValue::Array(pairs) = model else {
+        panic!("not an array at 0x{offset:x}")
+    };
+    let ids: Vec<_> = pairs
+        .iter()
+        .map(|pair| match pair {
+            DynamicValue::Struct(fields) => fields[0].1.clone(),
+            _ => panic!("not a struct"),
+        })
+        .collect();
+    let mut sorted = ids.clone();
+    sorted.sort_by_key(|id| match id {
+        DynamicValue::Primitive(PrimitiveValue::U16(id)) => *id,
+        _ => panic!("not an ID"),
+    });
+    assert_eq!(sorted, ids);
+}
+
+#[test]
+pub fn class_unlock_order() {
+    let mut save = SaveFile::from_bytes(SRC).unwrap();
+    let save = save.save_mut();
+    let (noah, class_id) = (NonZeroU32::new(1).unwrap(), NonZeroU32::new(13).unwrap());
+
+    let mut editor = ClassEditor::new(save, noah, class_id);
+    assert!(!editor.is_unlocked());
+    editor.set_rank(5);
+    editor.set_ascended(true);
+    assert!(editor.is_unlocked());
+    assert_eq!(5, editor.class().level);
+    assert!(editor.class().flags.is_set(ClassFlags::Ascended));
+
+    // Newly unlocked classes are the most recent in the order
+    let order = save.chronological_data.character_class_order(0);
+    assert_eq!(12, order.entries()[0]);
+    let value = order.get(12);
+
+    // Already listed classes keep their position
+    let mut editor = ClassEditor::new(save, noah, class_id);
+    editor.lock();
+    assert!(!editor.is_ascended());
+    editor.unlock();
+    assert_eq!(1, editor.rank());
+    assert_eq!(
+        value,
+        save.chronological_data.character_class_order(0).get(12)
+    );
+}
+
+#[test]
+pub fn class_unlock_order_dlc4() {
+    let mut save = SaveFile::from_bytes(SRC).unwrap();
+    let save = save.save_mut();
+    save.set_flag(SaveFlag::Dlc4, true);
+    let noah = NonZeroU32::new(1).unwrap();
+    let matthew = NonZeroU32::new(36).unwrap();
+
+    // Column 0 belongs to Matthew, so editing Noah's classes leaves it alone
+    let before = save.chronological_data.character_class_order(0).entries();
+    assert!(!before.contains(&36));
+    ClassEditor::new(save, noah, NonZeroU32::new(13).unwrap()).unlock();
+    assert_eq!(
+        before,
+        save.chronological_data.character_class_order(0).entries()
+    );
+
+    let mut editor = ClassEditor::new(save, matthew, NonZeroU32::new(37).unwrap());
+    editor.lock();
+    editor.unlock();
+    assert_eq!(
+        36,
+        save.chronological_data.character_class_order(0).entries()[0]
+    );
+}
@@ -0,0 +1,51 @@
+use recordkeeper::{diff, flags::FlagType, SaveFile};
+use recordkeeper_data_model::PrimitiveValue;
+
+static SRC: &[u8] = include_bytes!("res/save-ch5-v10.sav");
+
+#[test]
+pub fn no_changes() {
+    let save = SaveFile::from_bytes(SRC).unwrap();
+    assert!(diff::diff(save.save(), save.save()).unwrap().is_empty());
+}
+
+#[test]
+pub fn field_paths() {
+    let old = SaveFile::from_bytes(SRC).unwrap();
+    let mut new = SaveFile::from_bytes(SRC).unwrap();
+    let save = new.save_mut();
+
+    let level = save.characters[3].level;
+    save.characters[3].level = level + 1;
+    let flag = old.save().flags.get(FlagType::Bit, 4521).unwrap();
+    save.flags.set(FlagType::Bit, 4521, flag ^ 1);
+    let flag_8b = old.save().flags.get(FlagType::Byte, 10).unwrap();
+    save.flags.set(FlagType::Byte, 10, flag_8b ^ 0xff);
+
+    let changes = diff::diff(old.save(), new.save()).unwrap();
+    let paths: Vec<_> = changes.iter().map(|c| c.path.as_str()).collect();
+    assert_eq!(
+        vec![
+            "flags.flags_1b[4521]",
+            "flags.flags_8b[10]",
+            "characters[3].level"
+        ],
+        paths
+    );
+    assert_eq!(PrimitiveValue::U32(level), changes[2].old);
+    assert_eq!(PrimitiveValue::U32(level + 1), changes[2].new);
+    assert_eq!(PrimitiveValue::U32(flag), changes[0].old);
+}
+
+#[test]
+pub fn unknown_bytes() {
+    let old = SaveFile::from_bytes(SRC).unwrap();
+    let mut new = SRC.to_vec();
+    // Unmapped byte between the save version and play time
+    new[0x8] ^= 1;
+
+    let changes = diff::diff_bytes::<recordkeeper::SaveData>(old.bytes(), &new);
+    assert_eq!(1, changes.len());
+    assert_eq!("@0x8", changes[0].path);
+    assert_eq!(0x8, changes[0].offset);
+}
@@ -0,0 +1,277 @@
+use std::num::NonZeroU32;
+
+use recordkeeper::{
+    character::{
+        formation::{FormationName, PartyFormation},
+        portable::{MissingAccessory, PortableAccessory, PortableError, PortableFormation},
+    },
+    check::Problem,
+    error::SaveError,
+    item::{edit::ItemEditor, ItemType},
+    SaveFile,
+};
+
+static SRC: &[u8] = include_bytes!("res/save-ch5-v10.sav");
+
+#[test]
+pub fn apply_formation() {
+    let mut save = SaveFile::from_bytes(SRC).unwrap();
+    let save = save.save_mut();
+
+    let formation = PartyFormation::from_save(save, FormationName::default());
+    let party: Vec<u16> = save.party_characters.iter().copied().collect();
+    let noah = &save.characters[0];
+    let class_id = NonZeroU32::new(noah.selected_class.into()).unwrap();
+    let (costume, cp) = (noah.costume_id, noah.class_data(class_id).cp);
+    let ouro_arts = save.ouroboros[0].art_ids;
+
+    // Control stays with the same character, wherever it is in the party
+    save.party_characters.clear();
+    save.party_characters.try_push(party[2]).unwrap();
+    save.party_characters.try_push(1).unwrap();
+    save.controlled_character_idx = 0;
+    let noah = &mut save.characters[0];
+    noah.selected_class = 2;
+    noah.costume_id = 0;
+    noah.class_data_mut(class_id).cp = 0;
+    save.ouroboros[0].art_ids = [0; 5];
+
+    formation.apply_to(save).unwrap();
+    assert_eq!(
+        party,
+        save.party_characters.iter().copied().collect::<Vec<_>>()
+    );
+    assert_eq!(2, save.controlled_character_idx);
+    let noah = &save.characters[0];
+    assert_eq!(class_id.get(), u32::from(noah.selected_class));
+    assert_eq!(costume, noah.costume_id);
+    assert_eq!(cp, noah.class_data(class_id).cp);
+    assert_eq!(ouro_arts, save.ouroboros[0].art_ids);
+    assert!(save.check().is_empty());
+}
+
+#[test]
+pub fn apply_formation_missing_accessory() {
+    let mut save = SaveFile::from_bytes(SRC).unwrap();
+    let save = save.save_mut();
+
+    let formation = PartyFormation::from_save(save, FormationName::default());
+    let class_id = NonZeroU32::new(u32::from(save.characters[0].selected_class)).unwrap();
+    let accessory = save.characters[0]
+        .class_data(class_id)
+        .accessory_slot(0)
+        .get()
+        .unwrap();
+    ItemEditor::new(save, ItemType::Accessory, accessory.slot_index().into()).clear();
+    save.party_characters.clear();
+
+    let err = formation.apply_to(save).unwrap_err();
+    assert!(matches!(
+        err,
+        SaveError::FormationAccessory {
+            character_id: 1,
+            accessory: 0,
+            problem: Problem::AccessorySlotEmpty(_),
+        }
+    ));
+    // Nothing was applied
+    assert!(save.party_characters.is_empty());
+
+    let mut empty = PartyFormation::from_save(save, FormationName::default());
+    empty.clear();
+    assert!(matches!(
+        empty.apply_to(save),
+        Err(SaveError::EmptyFormation)
+    ));
+}
+
+#[test]
+pub fn apply_invalid_formation() {
+    let mut save = SaveFile::from_bytes(SRC).unwrap();
+    let save = save.save_mut();
+    let formation = || PartyFormation::from_save(save, FormationName::default());
+
+    let mut duplicate = formation();
+    duplicate.party.try_push(1).unwrap();
+    let mut unselectable = formation();
+    unselectable.party.try_push(60).unwrap();
+    let mut character = formation();
+    character.characters[0].character_id = 65;
+    let mut class = formation();
+    class.characters[0].current_class = 65;
+    let mut ouroboros = formation();
+    ouroboros.ouroboros[0].ouroboros_id = 7;
+
+    save.party_characters.clear();
+    assert!(matches!(
+        duplicate.apply_to(save),
+        Err(SaveError::AlreadyInParty(1))
+    ));
+    assert!(matches!(
+        unselectable.apply_to(save),
+        Err(SaveError::UnselectableCharacter(60))
+    ));
+    assert!(matches!(
+        character.apply_to(save),
+        Err(SaveError::InvalidFormationCharacter(65))
+    ));
+    assert!(matches!(
+        class.apply_to(save),
+        Err(SaveError::InvalidFormationClass {
+            character_id: 1,
+            class_id: 65
+        })
+    ));
+    assert!(matches!(
+        ouroboros.apply_to(save),
+        Err(SaveError::InvalidFormationOuroboros(7))
+    ));
+    // Nothing was applied
+    assert!(save.party_characters.is_empty());
+}
+
+#[test]
+pub fn share_code_round_trip() {
+    let save = SaveFile::from_bytes(SRC).unwrap();
+    let save = save.save();
+
+    let formation = PartyFormation::from_save(save, FormationName::default());
+    let portable = PortableFormation::from_formation(&formation);
+    let code = portable.to_share_code().unwrap();
+    assert_eq!(portable, PortableFormation::from_share_code(&code).unwrap());
+
+    assert!(matches!(
+        PortableFormation::from_share_code(&code[..code.len() - 8]),
+        Err(PortableError::Truncated)
+    ));
+    assert!(matches!(
+        PortableFormation::from_share_code("AA"),
+        Err(PortableError::UnsupportedVersion(0))
+    ));
+}
+
+#[test]
+pub fn import_formation() {
+    let mut save = SaveFile::from_bytes(SRC).unwrap();
+    let save = save.save_mut();
+
+    let formation = PartyFormation::from_save(save, FormationName::default());
+    let portable = PortableFormation::from_formation(&formation);
+
+    let resolved = portable.resolve(save).unwrap();
+    assert!(resolved.missing.is_empty());
+    resolved.formation.apply_to(save).unwrap();
+    assert!(save.check().is_empty());
+
+    // Accessories that are no longer in the inventory are reported
+    let class_id = NonZeroU32::new(u32::from(save.characters[0].selected_class)).unwrap();
+    let item_id = save.characters[0]
+        .class_data(class_id)
+        .accessory_slot(0)
+        .get()
+        .unwrap()
+        .bdat_id();
+    let inventory = &mut save.inventory;
+    inventory.remove_item(&mut save.accessory_crafting, item_id, u16::MAX);
+    assert!(inventory.find_slot(item_id).is_none());
+
+    let expected: Vec<_> = portable
+        .characters
+        .iter()
+        .flat_map(|c| {
+            c.class
+                .accessories
+                .iter()
+                .enumerate()
+                .filter(|(_, a)| **a == Some(PortableAccessory::Inventory(item_id)))
+                .map(|(slot, _)| MissingAccessory {
+                    character_id: c.character_id,
+                    slot,
+                    item_id,
+                })
+        })
+        .collect();
+    let resolved = portable.resolve(save).unwrap();
+    assert_eq!(expected, resolved.missing);
+    assert!(resolved.missing.contains(&MissingAccessory {
+        character_id: 1,
+        slot: 0,
+        item_id,
+    }));
+    let noah = &resolved.formation.characters[0];
+    assert_eq!(1, noah.character_id);
+    assert!(noah.class.accessory_slot(0).get().is_none());
+    resolved.formation.apply_to(save).unwrap();
+}
+
+#[test]
+pub fn import_invalid_formation() {
+    let save = SaveFile::from_bytes(SRC).unwrap();
+    let save = save.save();
+    let formation = PartyFormation::from_save(save, FormationName::default());
+    let portable = PortableFormation::from_formation(&formation);
+
+    // Share codes are untrusted, so invalid formations must be rejected when resolving
+    let resolve_modified = |edit: &dyn Fn(&mut PortableFormation)| {
+        let mut modified = portable.clone();
+        edit(&mut modified);
+        let code = modified.to_share_code().unwrap();
+        PortableFormation::from_share_code(&code)
+            .unwrap()
+            .resolve(save)
+            .unwrap_err()
+    };
+    assert!(matches!(
+        resolve_modified(&|f| f.party = (1..=20).collect()),
+        PortableError::PartyTooLarge(20)
+    ));
+    assert!(matches!(
+        resolve_modified(&|f| f.party[0] = 0),
+        PortableError::InvalidCharacter(0)
+    ));
+    assert!(matches!(
+        resolve_modified(&|f| f.characters[0].character_id = 200),
+        PortableError::InvalidCharacter(200)
+    ));
+    assert!(matches!(
+        resolve_modified(&|f| f.characters[0].class_id = 65),
+        PortableError::InvalidClass(65)
+    ));
+    assert!(matches!(
+        resolve_modified(&|f| f.ouroboros[0].ouroboros_id = 7),
+        PortableError::InvalidOuroboros(7)
+    ));
+    assert!(matches!(
+        resolve_modified(&|f| f.party[1] = f.party[0]),
+        PortableError::DuplicateCharacter(1)
+    ));
+    assert!(matches!(
+        resolve_modified(&|f| f.party[0] = 60),
+        PortableError::UnselectableCharacter(60)
+    ));
+    assert!(matches!(
+        resolve_modified(&|f| {
+            f.characters[0].class.accessories[2] = Some(PortableAccessory::Builtin {
+                bdat_id: 1,
+                slot_index: 0,
+                item_type: ItemType::Accessory as u16,
+            })
+        }),
+        PortableError::InvalidBuiltinAccessory {
+            character_id: 1,
+            slot: 2
+        }
+    ));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+pub fn json_round_trip() {
+    let save = SaveFile::from_bytes(SRC).unwrap();
+    let formation = PartyFormation::from_save(save.save(), FormationName::default());
+    let portable = PortableFormation::from_formation(&formation);
+
+    let json = serde_json::to_value(&portable).unwrap();
+    assert_eq!(PortableFormation::VERSION, json["version"]);
+    assert_eq!(portable, serde_json::from_value(json).unwrap());
+}
@@ -0,0 +1,191 @@
+use std::{cmp::Ordering, num::NonZeroU32};
+
+use recordkeeper::{
+    chrono::ChronologicalOrder,
+    dlc::CRAFTED_ITEM_ID,
+    error::SaveError,
+    item::{compact::SlotOrder, edit::ItemEditor, ItemSlot, ItemType},
+    SaveData, SaveFile,
+};
+
+static SRC: &[u8] = include_bytes!("res/save-ch5-v10.sav");
+
+#[test]
+pub fn item_types_match_slots() {
+    let save = SaveFile::from_bytes(SRC).unwrap();
+    let inventory = &save.save().inventory;
+
+    for item_type in [
+        ItemType::Cylinder,
+        ItemType::Gem,
+        ItemType::Collection,
+        ItemType::Info,
+        ItemType::Accessory,
+        ItemType::Precious,
+    ] {
+        for slot in inventory.slots(item_type).iter().filter(|s| s.is_valid()) {
+            assert_eq!(Some(item_type), ItemType::get_by_item_id(slot.item_id()));
+        }
+    }
+}
+
+#[test]
+pub fn chronological_reorder() {
+    let mut save = SaveFile::from_bytes(SRC).unwrap();
+    let inventory = &mut save.save_mut().inventory;
+
+    let newest: Vec<u16> = inventory
+        .slots_chronological(ItemType::Gem)
+        .into_iter()
+        .map(ItemSlot::item_id)
+        .collect();
+    let (first, last) = (newest[0], newest[newest.len() - 1]);
+    assert_eq!(
+        Ordering::Less,
+        inventory.cmp_entries(first.into(), last.into())
+    );
+
+    inventory.swap(first.into(), last.into());
+    assert_eq!(
+        Ordering::Greater,
+        inventory.cmp_entries(first.into(), last.into())
+    );
+
+    inventory.insert(first.into());
+    let newest = inventory.slots_chronological(ItemType::Accessory);
+    let newest_gem = inventory.slots_chronological(ItemType::Gem);
+    assert_eq!(first, newest_gem[0].item_id());
+    assert!(newest.iter().all(|s| inventory
+        .cmp_entries(first.into(), s.item_id().into())
+        .is_lt()));
+}
+
+#[test]
+pub fn add_remove_items() {
+    let mut save = SaveFile::from_bytes(SRC).unwrap();
+    let save = save.save_mut();
+    let (inventory, crafting) = (&mut save.inventory, &mut save.accessory_crafting);
+
+    // New stack
+    let gem = 12196;
+    assert!(inventory.find_slot(gem).is_none());
+    let slot = inventory.add_item(crafting, gem, 2).unwrap();
+    assert_eq!(slot, inventory.find_slot(gem).unwrap().index() as usize);
+    assert_eq!(
+        gem,
+        inventory.slots_chronological(ItemType::Gem)[0].item_id()
+    );
+
+    // Existing stack. Every addition bumps the chronological counter, so the most
+    // recently added item is always the newest
+    let other_gem = inventory.slots_chronological(ItemType::Gem)[1].item_id();
+    assert_eq!(slot, inventory.add_item(crafting, gem, 3).unwrap());
+    inventory.add_item(crafting, other_gem, 1).unwrap();
+    assert!(inventory.cmp_entries(other_gem.into(), gem.into()).is_lt());
+    assert_eq!(slot, inventory.add_item(crafting, gem, 1).unwrap());
+    assert!(inventory.cmp_entries(gem.into(), other_gem.into()).is_lt());
+    assert_eq!(6, inventory.find_slot(gem).unwrap().amount());
+
+    assert!(matches!(
+        inventory.add_item(crafting, gem, 0),
+        Err(SaveError::InvalidItemAmount { amount: 0, .. })
+    ));
+    assert_eq!(1, inventory.remove_item(crafting, gem, 1));
+
+    assert_eq!(4, inventory.remove_item(crafting, gem, 4));
+    assert_eq!(1, inventory.find_slot(gem).unwrap().amount());
+    assert_eq!(1, inventory.remove_item(crafting, gem, 4));
+    assert!(inventory.find_slot(gem).is_none());
+
+    // Crafted accessories don't stack
+    let a = inventory.add_item(crafting, CRAFTED_ITEM_ID, 1).unwrap();
+    let b = inventory.add_item(crafting, CRAFTED_ITEM_ID, 1).unwrap();
+    assert_ne!(a, b);
+    assert!(crafting.get_data(a).is_some());
+    assert!(crafting.get_data(b).is_some());
+    assert_eq!(2, inventory.remove_item(crafting, CRAFTED_ITEM_ID, 2));
+    assert!(crafting.get_data(a).is_none());
+    assert!(crafting.get_data(b).is_none());
+
+    assert!(matches!(
+        inventory.add_item(crafting, CRAFTED_ITEM_ID, 2),
+        Err(SaveError::InvalidItemAmount { amount: 2, .. })
+    ));
+    assert!(inventory.add_item(crafting, 0, 1).is_err());
+}
+
+#[test]
+pub fn compact_keeps_accessories() {
+    let mut save = SaveFile::from_bytes(SRC).unwrap();
+    let save = save.save_mut();
+
+    let equipped = |save: &SaveData| {
+        let mut equipped = Vec::new();
+        // Heroes' accessories don't point to the inventory
+        for character in save.characters.iter().take(6) {
+            for class_id in 1..=64 {
+                let class = character.class_data(NonZeroU32::new(class_id).unwrap());
+                for accessory in class.accessories().filter_map(|s| s.get()) {
+                    let slot = &save.inventory.accessories[accessory.slot_index() as usize];
+                    equipped.push((accessory.bdat_id(), slot.item_id()));
+                }
+            }
+        }
+        equipped
+    };
+
+    let before = equipped(save);
+    assert!(!before.is_empty());
+
+    save.compact_inventory(ItemType::Accessory, SlotOrder::Newest);
+    assert_eq!(before, equipped(save));
+
+    let accessories = &save.inventory.accessories;
+    let count = accessories.iter().filter(|s| s.is_valid()).count();
+    assert!(accessories[..count].iter().all(ItemSlot::is_valid));
+    assert!(accessories[..count]
+        .iter()
+        .enumerate()
+        .all(|(i, s)| s.index() as usize == i));
+    assert!(accessories.windows(2).take(count - 1).all(|w| save
+        .inventory
+        .cmp_entries(w[0].item_id().into(), w[1].item_id().into())
+        .is_le()));
+}
+
+#[test]
+pub fn crafting_garbage_collection() {
+    let mut save = SaveFile::from_bytes(SRC).unwrap();
+    let save = save.save_mut();
+
+    let a = save
+        .inventory
+        .add_item(&mut save.accessory_crafting, CRAFTED_ITEM_ID, 1)
+        .unwrap();
+    let b = save
+        .inventory
+        .add_item(&mut save.accessory_crafting, CRAFTED_ITEM_ID, 1)
+        .unwrap();
+    save.accessory_crafting.get_data_mut(b).unwrap().level = 5;
+    assert_eq!(2, save.accessory_crafting.used_count());
+    assert_eq!(
+        vec![a, b],
+        save.accessory_crafting
+            .iter()
+            .map(|(slot, _)| slot)
+            .collect::<Vec<_>>()
+    );
+
+    // Replacing the item leaves the crafted data behind
+    ItemEditor::new(save, ItemType::Accessory, a)
+        .set_item_id(1)
+        .unwrap();
+    let crafting = &mut save.accessory_crafting;
+    assert_eq!(vec![a], crafting.dangling_offsets(&save.inventory));
+
+    assert_eq!(299, crafting.compact(&save.inventory));
+    assert!(crafting.dangling_offsets(&save.inventory).is_empty());
+    assert!(crafting.get_data(a).is_none());
+    assert_eq!(5, crafting.get_data(b).unwrap().level);
+    assert!(crafting.orphaned_data().is_empty());
+}
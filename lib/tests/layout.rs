@@ -0,0 +1,7 @@
+#[test]
+pub fn layout_checks() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/layout-gap.rs");
+    t.compile_fail("tests/ui/layout-overlap.rs");
+    t.compile_fail("tests/ui/layout-too-large.rs");
+}
@@ -0,0 +1,303 @@
+use std::num::NonZeroU32;
+
+use recordkeeper::{
+    character::{class::ClassFlags, CHARACTER_MAX},
+    dlc::ChallengeRank,
+    error::SaveError,
+    io::SaveBin,
+    SaveData, SaveFile, SaveFlag, SystemData,
+};
+use recordkeeper_data_model::{DynamicValue, Model, PrimitiveValue, TypeModel};
+
+static SRC: &[u8] = include_bytes!("res/save-ch5-v10.sav");
+
+fn field<'a>(value: &'a mut DynamicValue, name: &str) -> &'a mut DynamicValue {
+    let DynamicValue::Struct(fields) = value else {
+        panic!("not a struct")
+    };
+    &mut fields.iter_mut().find(|(n, _)| *n == name).unwrap().1
+}
+
+#[test]
+pub fn model_sizes() {
+    assert_eq!(SaveData::size(), SaveData::model().size());
+    assert_eq!(SystemData::size(), SystemData::model().size());
+}
+
+#[test]
+pub fn dynamic_round_trip() {
+    let save = SaveFile::from_bytes(SRC).unwrap();
+    let model = SaveData::model();
+
+    let mut value = model.decode(SRC).unwrap();
+    assert_eq!(
+        &DynamicValue::Primitive(PrimitiveValue::U32(save.save().gold)),
+        field(&mut value, "gold")
+    );
+
+    let mut bytes = SRC.to_vec();
+    value.encode(&model, &mut bytes).unwrap();
+    assert_eq!(SRC, bytes);
+
+    *field(&mut value, "gold") = DynamicValue::Primitive(PrimitiveValue::U32(12345));
+    let DynamicValue::Array(characters) = field(&mut value, "characters") else {
+        panic!("not an array")
+    };
+    *field(&mut characters[3], "level") = DynamicValue::Primitive(PrimitiveValue::U32(99));
+    value.encode(&model, &mut bytes).unwrap();
+
+    let edited = SaveFile::from_bytes(&bytes).unwrap();
+    assert_eq!(12345, edited.save().gold);
+    assert_eq!(99, edited.save().characters[3].level);
+
+    // Wrong primitive type
+    *field(&mut value, "gold") = DynamicValue::Primitive(PrimitiveValue::U16(1));
+    assert!(value.encode(&model, &mut bytes).is_err());
+}
+
+#[test]
+pub fn path_get_set() {
+    let mut save = SaveFile::from_bytes(SRC).unwrap();
+    let level = save.save().characters[0].level;
+    assert_eq!(
+        PrimitiveValue::U32(level),
+        save.get_path("characters[0].level").unwrap()
+    );
+
+    // Pending changes are only visible after they are written
+    let gold = save.save().gold;
+    save.save_mut().gold = 500;
+    assert_eq!(PrimitiveValue::U32(gold), save.get_path("gold").unwrap());
+    save.write().unwrap();
+    assert_eq!(PrimitiveValue::U32(500), save.get_path("gold").unwrap());
+
+    save.set_path("characters[0].level", PrimitiveValue::U32(level + 1))
+        .unwrap();
+    assert_eq!(level + 1, save.save().characters[0].level);
+    assert_eq!(500, save.save().gold);
+
+    // Type and bounds checking
+    assert!(save
+        .set_path("characters[0].level", PrimitiveValue::U8(1))
+        .is_err());
+    assert!(save.get_path("characters[1000].level").is_err());
+    assert!(save.get_path("characters[0]").is_err());
+    assert!(save.get_path("characters[0].unknown").is_err());
+
+    // Dynamic arrays are bounded by their length, not their capacity
+    let party_len = save.save().party_characters.len();
+    assert!(save
+        .get_path(&format!("party_characters[{}]", party_len - 1))
+        .is_ok());
+    assert!(save
+        .get_path(&format!("party_characters[{party_len}]"))
+        .is_err());
+    let party = &mut save.save_mut().party_characters;
+    while party.len() > 3 {
+        party.try_pop().unwrap();
+    }
+    assert!(save
+        .set_path("party_characters[5]", PrimitiveValue::U16(1))
+        .is_err());
+    assert_eq!(3, save.save().party_characters.len());
+
+    // Parse errors revert the change
+    assert!(save
+        .set_path("save_version", PrimitiveValue::U8(1))
+        .is_err());
+    assert_eq!(
+        PrimitiveValue::U8(10),
+        save.get_path("save_version").unwrap()
+    );
+}
+
+#[test]
+pub fn enum_fields() {
+    let TypeModel::Enum(model) = ChallengeRank::model() else {
+        panic!("not an enum")
+    };
+    assert_eq!("u32", model.repr);
+    assert_eq!(Some("S"), model.variant_name(1));
+    assert_eq!(None, model.variant_name(5));
+    assert_eq!(ChallengeRank::C, ChallengeRank::try_from(4).unwrap());
+
+    let path = "challenge_battle.challenges_1_18[0].ranks[1]";
+    let mut save = SaveFile::from_bytes(SRC).unwrap();
+    save.set_path(path, PrimitiveValue::U32(2)).unwrap();
+    assert_eq!(PrimitiveValue::U32(2), save.get_path(path).unwrap());
+
+    // Unknown discriminants are rejected
+    assert!(save.set_path(path, PrimitiveValue::U32(5)).is_err());
+    assert_eq!(PrimitiveValue::U32(2), save.get_path(path).unwrap());
+
+    let (offset, _) = SaveData::model().find_path(path).unwrap();
+    let mut bytes = SRC.to_vec();
+    bytes[offset] = 5;
+    let Err(err @ SaveError::Field { .. }) = SaveFile::from_bytes(&bytes) else {
+        panic!("expected field error")
+    };
+    let SaveError::Discriminant(inner) = err.inner() else {
+        panic!("unexpected error {err}")
+    };
+    assert_eq!(("ChallengeRank", 5), (inner.enum_name, inner.value));
+    let SaveError::Field {
+        path: err_path,
+        offset: err_offset,
+        ..
+    } = &err
+    else {
+        unreachable!()
+    };
+    assert_eq!((path, offset as u64), (err_path.as_str(), *err_offset));
+}
+
+#[test]
+pub fn named_flags() {
+    let mut save = SaveFile::from_bytes(SRC).unwrap();
+    let data = save.save_mut();
+    data.set_flag(SaveFlag::Gauntlet, true);
+    data.set_flag(SaveFlag::TimeLocked, false);
+    assert!(data.save_flags.iter().any(|f| f == SaveFlag::Gauntlet));
+    assert!(data.save_flags.iter().all(|f| f != SaveFlag::TimeLocked));
+
+    let class = data.characters[0].class_data_mut(NonZeroU32::new(1).unwrap());
+    class.flags.set(ClassFlags::Ascended, true);
+    class.flags.set(ClassFlags::Grow13, false);
+    class.flags.set(ClassFlags::Unk, true);
+    assert_eq!(
+        vec![ClassFlags::Ascended, ClassFlags::Unk],
+        class.flags.iter().collect::<Vec<_>>()
+    );
+
+    save.write().unwrap();
+    let path = "characters[0].class_inventory[0].flags";
+    let PrimitiveValue::U8(raw) = save.get_path(path).unwrap() else {
+        panic!("not a u8")
+    };
+    assert_eq!(0b101, raw & 0b111);
+
+    let save_model = SaveData::model();
+    let (_, TypeModel::Flags(model)) = save_model.find_path(path).unwrap() else {
+        panic!("not flags")
+    };
+    assert_eq!("ClassFlags", model.name);
+    assert!(model.flags.contains(&("Unk", 2)));
+}
+
+#[test]
+pub fn semantic_models() {
+    let save = SaveFile::from_bytes(SRC).unwrap();
+    let model = SaveData::model();
+
+    let (offset, party) = model.find_path("party_characters").unwrap();
+    let TypeModel::Vec(vec_model) = party else {
+        panic!("not a vector")
+    };
+    assert_eq!(
+        ("u16", "u64"),
+        (vec_model.item.to_string().as_str(), vec_model.len_type)
+    );
+    let expected = save
+        .save()
+        .party_characters
+        .iter()
+        .map(|&c| DynamicValue::Primitive(PrimitiveValue::U16(c)))
+        .collect();
+    assert_eq!(
+        DynamicValue::Array(expected),
+        party.decode(&SRC[offset..]).unwrap()
+    );
+
+    // Vectors are encoded with their length
+    let mut bytes = SRC.to_vec();
+    let new_party = DynamicValue::Array(vec![DynamicValue::Primitive(PrimitiveValue::U16(1))]);
+    new_party.encode(party, &mut bytes[offset..]).unwrap();
+    assert_eq!(
+        &[1],
+        &*SaveFile::from_bytes(&bytes)
+            .unwrap()
+            .save()
+            .party_characters
+            .iter()
+            .copied()
+            .collect::<Vec<_>>()
+    );
+    let too_long = DynamicValue::Array(vec![
+        DynamicValue::Primitive(PrimitiveValue::U16(1));
+        vec_model.capacity + 1
+    ]);
+    assert!(too_long.encode(party, &mut bytes[offset..]).is_err());
+
+    let (offset, mode) = model.find_path("play_report_stats.product_mode").unwrap();
+    assert!(matches!(mode, TypeModel::String(s) if s.len_type == Some("u32")));
+    assert_eq!(
+        DynamicValue::String("product".to_string()),
+        mode.decode(&SRC[offset..]).unwrap()
+    );
+
+    let (offset, flags) = model.find_path("flags.flags_2b").unwrap();
+    let TypeModel::BitFlags(flags_model) = flags else {
+        panic!("not bit flags")
+    };
+    assert_eq!(2, flags_model.bits);
+    let DynamicValue::Array(values) = flags.decode(&SRC[offset..]).unwrap() else {
+        panic!("not an array")
+    };
+    assert_eq!(flags_model.len(), values.len());
+}
+
+#[test]
+pub fn field_annotations() {
+    let TypeModel::Struct(model) = SaveData::model() else {
+        panic!("not a struct")
+    };
+    let annotations = |name| {
+        &model
+            .fields
+            .iter()
+            .find(|f| f.name == name)
+            .unwrap()
+            .annotations
+    };
+
+    let weather = annotations("weather");
+    assert_eq!(Some("RSC_WeatherSet"), weather.table);
+    assert!(weather.doc.unwrap().starts_with("ID for `RSC_WeatherSet`."));
+    // Doc lines are kept separate
+    assert_eq!(2, weather.doc.unwrap().lines().count());
+
+    let ether = annotations("ether_cylinder_progress");
+    assert_eq!(Some(0.0..=100.0), ether.range);
+    assert_eq!(None, ether.table);
+
+    assert!(annotations("gold").is_empty());
+}
+
+#[test]
+pub fn unmapped_ranges() {
+    let TypeModel::Struct(model) = SaveData::model() else {
+        panic!("not a struct")
+    };
+    let ranges = model.unmapped_ranges();
+
+    // Between the save version and play time
+    let first = &ranges[0];
+    assert_eq!(
+        ("", "SaveData", 5, 11),
+        (
+            first.path.as_str(),
+            first.parent,
+            first.relative_offset,
+            first.size
+        )
+    );
+    assert_eq!(vec![5], first.offsets);
+    assert!(!first.is_non_zero(SRC));
+
+    // Ranges in array items are repeated for each item
+    let character = ranges.iter().find(|r| r.path == "characters[]").unwrap();
+    assert_eq!(CHARACTER_MAX, character.offsets.len());
+    assert!(ranges
+        .iter()
+        .all(|r| r.offsets.iter().all(|o| o + r.size <= SaveData::SIZE)));
+}
@@ -0,0 +1,106 @@
+use recordkeeper::{
+    character::party::{CharacterUnlock, PartyEditor},
+    error::SaveError,
+    SaveFile,
+};
+
+static SRC: &[u8] = include_bytes!("res/save-ch5-v10.sav");
+
+#[test]
+pub fn edit_party() {
+    let mut save = SaveFile::from_bytes(SRC).unwrap();
+    let save = save.save_mut();
+
+    let mut party = PartyEditor::new(save);
+    assert_eq!(
+        vec![1, 2, 3, 4, 5, 6, 21],
+        party.members().collect::<Vec<_>>()
+    );
+    party.set_controlled(3).unwrap();
+
+    // Control follows the character when the party is rearranged
+    party.set_leader(21).unwrap();
+    party.move_member(3, 6).unwrap();
+    assert_eq!(Some(21), party.leader());
+    assert_eq!(Some(3), party.controlled());
+    party.remove(2).unwrap();
+    assert_eq!(Some(3), party.controlled());
+    party.remove(3).unwrap();
+    assert_eq!(Some(21), party.controlled());
+
+    assert!(matches!(party.add(1), Err(SaveError::AlreadyInParty(1))));
+    assert!(matches!(
+        party.add(11),
+        Err(SaveError::UnselectableCharacter(11))
+    ));
+    assert!(matches!(party.remove(3), Err(SaveError::NotInParty(3))));
+    party.add(9).unwrap();
+
+    party.add_guest(1).unwrap();
+    assert!(matches!(
+        party.add_guest(1),
+        Err(SaveError::AlreadyInParty(1))
+    ));
+    party.remove_guest(625).unwrap();
+    assert_eq!(vec![626, 628, 34, 1], party.guests().collect::<Vec<_>>());
+
+    assert_eq!(
+        vec![21, 1, 4, 5, 6, 9],
+        save.party_characters.iter().copied().collect::<Vec<_>>()
+    );
+    assert_eq!(0, save.controlled_character_idx);
+    assert!(save.check().is_empty());
+}
+
+#[test]
+pub fn keep_last_member() {
+    let mut save = SaveFile::from_bytes(SRC).unwrap();
+    let save = save.save_mut();
+
+    let mut party = PartyEditor::new(save);
+    for id in [1, 2, 3, 4, 5, 6] {
+        party.remove(id).unwrap();
+    }
+    assert!(matches!(
+        party.remove(21),
+        Err(SaveError::LastPartyMember(21))
+    ));
+    assert!(matches!(
+        party.lock_character(21),
+        Err(SaveError::LastPartyMember(21))
+    ));
+    assert!(party.character_sets().is_selectable(21));
+    assert_eq!(vec![21], party.members().collect::<Vec<_>>());
+    assert!(save.check().is_empty());
+}
+
+#[test]
+pub fn unlock_characters() {
+    let mut save = SaveFile::from_bytes(SRC).unwrap();
+    let save = save.save_mut();
+
+    assert!(!save.chronological_data.unlocked_characters.is_present(30));
+    let mut party = PartyEditor::new(save);
+    party.unlock_character(31, CharacterUnlock::Temporary);
+    party.add(31).unwrap();
+    party.set_controlled(31).unwrap();
+
+    let sets = party.character_sets();
+    assert!(sets.is_selectable(31) && sets.is_temporary(31) && !sets.is_permanent(31));
+    let order = &save.chronological_data.unlocked_characters;
+    let max = order.get(30).unwrap();
+    assert!((0..64).all(|id| order.get(id).unwrap_or_default() <= max));
+
+    // Locked characters leave the party, but keep their unlock order
+    let mut party = PartyEditor::new(save);
+    party.lock_character(31).unwrap();
+    assert!(!party.contains(31));
+    assert_eq!(Some(1), party.controlled());
+    party.unlock_character(31, CharacterUnlock::Permanent);
+    assert!(party.character_sets().is_permanent(31));
+    assert_eq!(
+        Some(max),
+        save.chronological_data.unlocked_characters.get(30)
+    );
+    assert!(save.check().is_empty());
+}
@@ -0,0 +1,47 @@
+#![cfg(feature = "serde")]
+
+use std::thread;
+
+use recordkeeper::{SaveData, SaveFile};
+
+static SRC: &[u8] = include_bytes!("res/save-ch5-v10.sav");
+
+#[test]
+pub fn json_round_trip() {
+    let save = SaveFile::from_bytes(SRC).unwrap();
+    let mut value = serde_json::to_value(save.save()).unwrap();
+    value["gold"] = 12345.into();
+
+    let data: SaveData = deserialize(value).unwrap();
+    assert_eq!(12345, data.gold);
+
+    let mut save = save;
+    *save.save_mut() = data;
+    save.write().unwrap();
+
+    let mut original = SaveFile::from_bytes(SRC).unwrap();
+    original.save_mut().gold = 12345;
+    original.write().unwrap();
+    assert_eq!(original.bytes(), save.bytes());
+}
+
+#[test]
+pub fn json_version_check() {
+    let save = SaveFile::from_bytes(SRC).unwrap();
+    let mut value = serde_json::to_value(save.save()).unwrap();
+    value["save_version"] = 9.into();
+
+    let err = deserialize(value).err().unwrap();
+    assert!(err.to_string().contains("Unsupported version 9"));
+}
+
+/// Debug builds need extra stack space to deserialize the whole save, see
+/// `SerdeBin`.
+fn deserialize(value: serde_json::Value) -> serde_json::Result<SaveData> {
+    thread::Builder::new()
+        .stack_size(8 * 1024 * 1024)
+        .spawn(move || serde_json::from_value(value))
+        .unwrap()
+        .join()
+        .unwrap()
+}
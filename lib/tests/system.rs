@@ -13,3 +13,11 @@ pub fn write_back_no_changes() {
     save.write().unwrap();
     assert_eq!(SRC, save.bytes());
 }
+
+#[test]
+pub fn new_system() {
+    let system = SystemFile::new();
+    let bytes = system.to_bytes().unwrap();
+    assert_eq!(SRC.len(), bytes.len());
+    assert!(DataFile::from_bytes(&bytes).unwrap().is_system());
+}
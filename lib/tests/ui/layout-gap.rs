@@ -0,0 +1,14 @@
+use recordkeeper_macros::SaveBin;
+
+// Fields may be placed after a gap, and the struct may be larger than its fields
+#[derive(SaveBin)]
+#[size(8)]
+struct Header {
+    magic: u32,
+    #[loc(0x6)]
+    version: u16,
+}
+
+fn main() {
+    assert_eq!(8, <Header as recordkeeper::io::SaveBin>::SIZE);
+}
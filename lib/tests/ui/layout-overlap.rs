@@ -0,0 +1,10 @@
+use recordkeeper_macros::SaveBin;
+
+#[derive(SaveBin)]
+struct Header {
+    magic: u32,
+    #[loc(0x2)]
+    version: u16,
+}
+
+fn main() {}
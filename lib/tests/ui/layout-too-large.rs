@@ -0,0 +1,10 @@
+use recordkeeper_macros::SaveBin;
+
+#[derive(SaveBin)]
+#[size(4)]
+struct Header {
+    magic: u32,
+    version: u16,
+}
+
+fn main() {}
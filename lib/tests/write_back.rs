@@ -1,4 +1,7 @@
-use recordkeeper::{DataFile, SaveFile};
+use recordkeeper::{
+    diff, error::SaveError, io::SaveBin, DataFile, FileHeader, FileKind, SaveData, SaveFile,
+};
+use recordkeeper_data_model::Model;
 
 static SRC: &[u8] = include_bytes!("res/save-ch5-v10.sav");
 
@@ -13,3 +16,97 @@ pub fn write_back_no_changes() {
     save.write().unwrap();
     assert_eq!(SRC, save.bytes());
 }
+
+#[test]
+pub fn write_from_scratch() {
+    let save = SaveFile::from_bytes(SRC).unwrap();
+    let bytes = save.to_bytes().unwrap();
+    assert_eq!(SRC.len(), bytes.len());
+
+    let copy = SaveFile::from_bytes(&bytes).unwrap();
+    assert!(diff::diff(save.save(), copy.save()).unwrap().is_empty());
+}
+
+#[test]
+pub fn zeroed_save() {
+    let mut save = SaveFile::zeroed();
+    assert!(save.save().check().is_empty());
+    save.save_mut().gold = 100;
+
+    let save = SaveFile::from_bytes(&save.to_bytes().unwrap()).unwrap();
+    assert_eq!(100, save.save().gold);
+    assert!(save.bytes()[0x8..0x10].iter().all(|&b| b == 0));
+}
+
+#[test]
+pub fn unsupported_version_header() {
+    let header = FileHeader::read(SRC).unwrap();
+    assert_eq!(FileKind::Save, header.kind);
+    assert_eq!(10, header.version);
+    assert!(header.is_supported());
+
+    let mut old = SRC.to_vec();
+    old[4] = 9;
+    let header = FileHeader::read(&old).unwrap();
+    assert_eq!(9, header.version);
+    assert!(!header.is_supported());
+    // Older versions can be identified, but not parsed
+    let Err(err) = SaveFile::from_bytes(&old) else {
+        panic!("older version was parsed")
+    };
+    assert!(matches!(err.inner(), SaveError::UnsupportedVersion(9, 10)));
+}
+
+#[test]
+pub fn error_location() {
+    let mut broken = SRC.to_vec();
+    // Expected to be 6
+    broken[0x193ed8] = 7;
+
+    let Err(SaveError::Field {
+        struct_name,
+        field,
+        path,
+        offset,
+        source,
+    }) = SaveFile::from_bytes(&broken)
+    else {
+        panic!("expected field error")
+    };
+    assert_eq!("ChallengeBattle", struct_name);
+    assert_eq!("_unk", field);
+    assert_eq!("challenge_battle._unk", path);
+    assert_eq!(0x193ed8, offset);
+    assert!(matches!(*source, SaveError::AssertionError(_)));
+
+    // Truncated in the middle of a character
+    let model = SaveData::model();
+    let (level_offset, _) = model.find_path("characters[2].level").unwrap();
+    let Err(SaveError::Field { path, offset, .. }) = SaveFile::from_bytes(&SRC[..level_offset + 1])
+    else {
+        panic!("expected field error")
+    };
+    assert_eq!("characters[2].level", path);
+    assert_eq!(level_offset as u64, offset);
+}
+
+#[test]
+pub fn write_short_buffer() {
+    let save = SaveFile::from_bytes(SRC).unwrap();
+    let model = SaveData::model();
+    let (level_offset, _) = model.find_path("characters[2].level").unwrap();
+
+    let mut bytes = vec![0; level_offset + 1];
+    let Err(SaveError::Field {
+        path,
+        offset,
+        source,
+        ..
+    }) = save.save().write(&mut bytes)
+    else {
+        panic!("expected field error")
+    };
+    assert_eq!("characters[2].level", path);
+    assert_eq!(level_offset as u64, offset);
+    assert!(matches!(*source, SaveError::UnexpectedEof));
+}
@@ -0,0 +1,41 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields};
+
+pub fn derive_flag(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let item = parse_macro_input!(item as DeriveInput);
+    flag_tokens(&item).into()
+}
+
+/// Generates the `Flag` implementation for a fieldless enum. Discriminants are
+/// used as bit indices.
+fn flag_tokens(item: &DeriveInput) -> TokenStream {
+    let name = &item.ident;
+    let variants: Vec<_> = match &item.data {
+        Data::Enum(en) => en
+            .variants
+            .iter()
+            .map(|v| match v.fields {
+                Fields::Unit => &v.ident,
+                _ => panic!("Flag can only be derived on fieldless enums"),
+            })
+            .collect(),
+        _ => panic!("Flag can only be derived on enums"),
+    };
+
+    quote! {
+        impl crate::flags::Flag for #name {
+            const ALL: &'static [Self] = &[#(Self::#variants),*];
+
+            fn index(self) -> usize {
+                self as usize
+            }
+
+            fn name(self) -> &'static str {
+                match self {
+                    #(Self::#variants => stringify!(#variants),)*
+                }
+            }
+        }
+    }
+}
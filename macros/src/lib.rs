@@ -1,8 +1,14 @@
 use proc_macro::TokenStream;
 
+mod flag;
 mod save_bin;
 
-#[proc_macro_derive(SaveBin, attributes(loc, assert, size))]
+#[proc_macro_derive(SaveBin, attributes(loc, assert, size, manual_model, range, table))]
 pub fn derive_save_deserialize(item: TokenStream) -> TokenStream {
     save_bin::derive_save_deserialize(item)
 }
+
+#[proc_macro_derive(Flag)]
+pub fn derive_flag(item: TokenStream) -> TokenStream {
+    flag::derive_flag(item)
+}
@@ -1,14 +1,20 @@
 use proc_macro2::TokenStream;
 
-use quote::{quote, ToTokens};
+use quote::{format_ident, quote, ToTokens};
 use syn::punctuated::Punctuated;
-use syn::{parse_macro_input, Data, DeriveInput, Expr, Field, Meta, Token};
+use syn::{
+    parse_macro_input, parse_quote, Data, DataEnum, DeriveInput, Expr, ExprLit, Field, Fields,
+    Generics, Ident, Lit, LitStr, Meta, Token,
+};
 
 struct FieldVisitor<'ast> {
     field: &'ast Field,
     location: Option<TokenStream>,
     assert_value: Option<TokenStream>,
     assert_error: Option<TokenStream>,
+    doc: Option<String>,
+    range: Option<(Expr, Expr)>,
+    table: Option<LitStr>,
 }
 
 impl<'ast> FieldVisitor<'ast> {
@@ -17,47 +23,66 @@ impl<'ast> FieldVisitor<'ast> {
         let type_ident = &self.field.ty;
 
         let loc_code = self.location.as_ref().map(|loc| {
-            quote! {   
-                #[cfg(debug_assertions)]
-                {
-                    let current = __IN_BYTES.stream_position()? - __BASE_OFFSET;
-                    if #loc < current {
-                        panic!("New location 0x{:x} is lower than current location 0x{:x} for field {}",
-                            #loc, current, stringify!(#var_name));
-                    }
-                }
-                __IN_BYTES.seek(std::io::SeekFrom::Start(__BASE_OFFSET + #loc))?;  
-            }
-        });
-
-        let assert_error = self.assert_error.clone().unwrap_or_else(|| {
+            // Location order is checked at compile time, see size_calc_tokens
             quote! {
-                crate::error::SaveError::AssertionError(format!("(Actual) {:?} != (Expected) {:?}",
-                    ACTUAL, EXPECTED))
+                __IN_BYTES.seek(std::io::SeekFrom::Start(__BASE_OFFSET + #loc))?;
             }
         });
 
+        let assert_error = self.assert_error_tokens();
         let assert_code = self.assert_value.as_ref().map(|assert_value| {
             let field_type = self.field.ty.to_token_stream();
             quote! {
                 let EXPECTED: #field_type = #assert_value;
                 let ACTUAL = #var_name;
                 if EXPECTED != ACTUAL {
-                    return Err(#assert_error)
+                    return Err(::recordkeeper::error::SaveError::from(#assert_error)
+                        .in_field(__STRUCT_NAME, stringify!(#var_name), __POS_BEFORE_READ))
                 }
             }
         });
 
         quote! {
             #loc_code
-            let __SIZE = <#type_ident as crate::io::SaveBin>::size();
+            let __SIZE = <#type_ident as ::recordkeeper::io::SaveBin>::size();
             let __POS_BEFORE_READ = __IN_BYTES.stream_position()?;
-            let #var_name = <#type_ident as crate::io::SaveBin>::read(__IN_BYTES)?;
+            let #var_name = <#type_ident as ::recordkeeper::io::SaveBin>::read(__IN_BYTES).map_err(|e| {
+                ::recordkeeper::error::SaveError::from(e)
+                    .in_field(__STRUCT_NAME, stringify!(#var_name), __POS_BEFORE_READ)
+            })?;
             __IN_BYTES.seek(std::io::SeekFrom::Start(__POS_BEFORE_READ + __SIZE as u64))?;
             #assert_code
         }
     }
 
+    /// Generates the check for the `assert` attribute when deserializing with serde.
+    ///
+    /// Like the binary reader, deserialization fails if the value doesn't match.
+    fn serde_assert_tokens(&self, struct_name: &Ident) -> Option<TokenStream> {
+        let var_name = &self.field.ident;
+        let field_type = self.field.ty.to_token_stream();
+        let assert_value = self.assert_value.as_ref()?;
+        let assert_error = self.assert_error_tokens();
+        Some(quote! {
+            let EXPECTED: #field_type = #assert_value;
+            let ACTUAL = #var_name;
+            if EXPECTED != ACTUAL {
+                return Err(__A::Error::custom(format_args!("{}.{}: {}", stringify!(#struct_name),
+                    stringify!(#var_name), ::recordkeeper::error::SaveError::from(#assert_error))));
+            }
+            let #var_name = ACTUAL;
+        })
+    }
+
+    fn assert_error_tokens(&self) -> TokenStream {
+        self.assert_error.clone().unwrap_or_else(|| {
+            quote! {
+                ::recordkeeper::error::SaveError::AssertionError(format!("(Actual) {:?} != (Expected) {:?}",
+                    ACTUAL, EXPECTED))
+            }
+        })
+    }
+
     fn writer_tokens(&self) -> TokenStream {
         let name = &self.field.ident;
         let field_type = self.field.ty.to_token_stream();
@@ -70,29 +95,32 @@ impl<'ast> FieldVisitor<'ast> {
 
         quote! {
             #loc_code
-            let __TMP_BYTES = &mut __OUT_BYTES[__POS..];
-            self. #name .write(__TMP_BYTES)?;
-            __POS += <#field_type as crate::io::SaveBin>::size();
+            let __RESULT = match __OUT_BYTES.get_mut(__POS..) {
+                Some(__TMP_BYTES) => self. #name .write(__TMP_BYTES).map_err(::recordkeeper::error::SaveError::from),
+                None => Err(::recordkeeper::error::SaveError::UnexpectedEof),
+            };
+            __RESULT.map_err(|e| e.in_written_field(__STRUCT_NAME, stringify!(#name), __POS))?;
+            __POS += <#field_type as ::recordkeeper::io::SaveBin>::size();
         }
     }
 
+    /// Generates the code that computes the struct size in a const context.
+    ///
+    /// Layout errors cause const evaluation to fail, so they are reported at compile time.
     fn size_calc_tokens(&self) -> TokenStream {
         let type_ident = &self.field.ty;
         let field_name = self.field.ident.to_token_stream();
 
         match &self.location {
             Some(loc) => quote! {
-                #[cfg(debug_assertions)]
-                if #loc < current_loc {
-                    panic!("New location 0x{:x} is lower than current location 0x{:x} for field {}",
-                        #loc, current_loc, stringify!(#field_name));
-                }
-                let _size = <#type_ident as crate::io::SaveBin>::size();
+                assert!(#loc >= current_loc, concat!("Location for field ", stringify!(#field_name),
+                    " is lower than the end of the previous field"));
+                let _size = <#type_ident as ::recordkeeper::io::SaveBin>::SIZE;
                 size += _size + #loc - current_loc;
                 current_loc = #loc + _size;
             },
             None => quote! {
-                let _size = <#type_ident as crate::io::SaveBin>::size();
+                let _size = <#type_ident as ::recordkeeper::io::SaveBin>::SIZE;
                 size += _size;
                 current_loc += _size;
             },
@@ -103,9 +131,22 @@ impl<'ast> FieldVisitor<'ast> {
         let type_ident = &self.field.ty;
         let field_name = self.field.ident.to_token_stream();
 
+        let doc = option_tokens(self.doc.as_ref());
+        let table = option_tokens(self.table.as_ref());
+        let range = option_tokens(
+            self.range
+                .as_ref()
+                .map(|(min, max)| quote!((#min) as f64..=(#max) as f64)),
+        );
+
         let model_gen = quote! {
-            let _size = <#type_ident as crate::io::SaveBin>::size();
+            let _size = <#type_ident as ::recordkeeper::io::SaveBin>::size();
             let _type = <#type_ident as recordkeeper_data_model::Model>::model();
+            let _annotations = recordkeeper_data_model::FieldAnnotations {
+                doc: #doc,
+                range: #range,
+                table: #table,
+            };
         };
 
         match &self.location {
@@ -115,13 +156,13 @@ impl<'ast> FieldVisitor<'ast> {
                         #loc, current_loc, stringify!(#field_name));
                 }
                 #model_gen;
-                __BUILDER.add_field(stringify!(#field_name), _type, #loc, _size);
+                __BUILDER.add_annotated_field(stringify!(#field_name), _type, #loc, _size, _annotations);
                 size += _size + #loc - current_loc;
                 current_loc = #loc + _size;
             },
             None => quote! {
                 #model_gen;
-                __BUILDER.add_field(stringify!(#field_name), _type, current_loc, _size);
+                __BUILDER.add_annotated_field(stringify!(#field_name), _type, current_loc, _size, _annotations);
                 size += _size;
                 current_loc += _size;
             },
@@ -136,9 +177,10 @@ pub fn derive_save_deserialize(item: proc_macro::TokenStream) -> proc_macro::Tok
 
     let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
 
-    let item_struct = match item.data {
+    let item_struct = match &item.data {
         Data::Struct(str) => str,
-        _ => panic!("SaveBin can only be derived on structs"),
+        Data::Enum(en) => return enum_tokens(&item, en).into(),
+        _ => panic!("SaveBin can only be derived on structs and enums"),
     };
 
     let expected_size = item
@@ -150,6 +192,9 @@ pub fn derive_save_deserialize(item: proc_macro::TokenStream) -> proc_macro::Tok
             _ => panic!("syntax: #[size(N)]"),
         });
 
+    // The type implements Model by itself
+    let manual_model = item.attrs.iter().any(|a| a.path().is_ident("manual_model"));
+
     let field_visitors = item_struct
         .fields
         .iter()
@@ -157,11 +202,20 @@ pub fn derive_save_deserialize(item: proc_macro::TokenStream) -> proc_macro::Tok
             let mut loc = None;
             let mut assert = None;
             let mut assert_error = None;
+            let mut doc_lines = Vec::new();
+            let mut range = None;
+            let mut table = None;
 
             for attr in &f.attrs {
                 let path = attr.path();
                 let list = match &attr.meta {
                     Meta::List(list) => list,
+                    Meta::NameValue(nv) if path.is_ident("doc") => {
+                        if let Expr::Lit(ExprLit { lit: Lit::Str(line), .. }) = &nv.value {
+                            doc_lines.push(line.value());
+                        }
+                        continue;
+                    }
                     _ => continue,
                 };
                 if path.is_ident("loc") {
@@ -174,6 +228,17 @@ pub fn derive_save_deserialize(item: proc_macro::TokenStream) -> proc_macro::Tok
                     let mut parts = parts.into_iter();
                     assert = Some(parts.next().unwrap().into_token_stream());
                     assert_error = parts.next().map(ToTokens::into_token_stream);
+                } else if path.is_ident("range") {
+                    let syntax = "syntax: #[range(MIN, MAX)] (inclusive)";
+                    let parts: Punctuated<Expr, Token!(,)> =
+                        list.parse_args_with(Punctuated::parse_terminated).expect(syntax);
+                    let mut parts = parts.into_iter();
+                    match (parts.next(), parts.next(), parts.next()) {
+                        (Some(min), Some(max), None) => range = Some((min, max)),
+                        _ => panic!("{syntax}"),
+                    }
+                } else if path.is_ident("table") {
+                    table = Some(list.parse_args().expect("syntax: #[table(\"TABLE_NAME\")]"));
                 }
             }
 
@@ -182,6 +247,9 @@ pub fn derive_save_deserialize(item: proc_macro::TokenStream) -> proc_macro::Tok
                 location: loc,
                 assert_value: assert,
                 assert_error,
+                doc: doc_comment(&doc_lines),
+                range,
+                table,
             }
         })
         .collect::<Vec<_>>();
@@ -208,45 +276,56 @@ pub fn derive_save_deserialize(item: proc_macro::TokenStream) -> proc_macro::Tok
 
     let extra_size = expected_size.map(|size| {
         quote! {
-            #[cfg(debug_assertions)]
-            if size > #size {
-                panic!("Struct {} too large, can't add padding. Expected max {} bytes, found {}.",
-                    stringify!(#name), #size, size);
-            }
+            assert!(size <= #size, concat!("Struct ", stringify!(#name),
+                " is larger than its expected size, can't add padding"));
             size = #size;
         }
     });
 
+    // Associated constants are only evaluated when used, so make sure layout
+    // checks always run for non-generic structs.
+    let layout_check = item.generics.params.is_empty().then(|| {
+        quote! {
+            const _: usize = <#name as ::recordkeeper::io::SaveBin>::SIZE;
+        }
+    });
+
     let field_names: Vec<_> = field_visitors
         .iter()
         .map(|v| v.field.ident.as_ref().unwrap())
         .collect();
 
+    let serde_impl = serde_tokens(name, &item.generics, &field_visitors);
+    let model_impl =
+        (!manual_model).then(|| model_tokens(&item, model_generators, extra_size.clone()));
+
     let out = quote! {
-        impl #impl_generics crate::io::SaveBin for #name #ty_generics #where_clause {
-            type ReadError = crate::error::SaveError;
-            type WriteError = crate::error::SaveError;
+        impl #impl_generics ::recordkeeper::io::SaveBin for #name #ty_generics #where_clause {
+            type ReadError = ::recordkeeper::error::SaveError;
+            type WriteError = ::recordkeeper::error::SaveError;
 
             fn read(__IN_BYTES: &mut std::io::Cursor::<&[u8]>) -> Result<Self, Self::ReadError> {
                 use std::io::Seek;
                 // Set up relative positions for start of struct.
                 let __BASE_OFFSET = __IN_BYTES.stream_position()?;
+                let __STRUCT_NAME = stringify!(#name);
 
                 #parsers
 
-                let __SIZE = <Self as crate::io::SaveBin>::size();
+                let __SIZE = <Self as ::recordkeeper::io::SaveBin>::size();
                 __IN_BYTES.seek(std::io::SeekFrom::Start(__BASE_OFFSET + __SIZE as u64))?;
 
                 Ok(Self { #(#field_names),*})
             }
 
             fn write(&self, mut __OUT_BYTES: &mut [u8]) -> Result<(), Self::WriteError> {
+                let __STRUCT_NAME = stringify!(#name);
                 let mut __POS = 0;
                 #writers
                 Ok(())
             }
 
-            fn size() -> usize { // TODO: const?
+            const SIZE: usize = {
                 let mut current_loc: usize = 0;
                 let mut size: usize = 0;
 
@@ -254,9 +333,46 @@ pub fn derive_save_deserialize(item: proc_macro::TokenStream) -> proc_macro::Tok
                 #extra_size
 
                 size
-            }
+            };
         }
 
+        #layout_check
+
+        #model_impl
+
+        #serde_impl
+    };
+
+    out.into()
+}
+
+/// Joins doc comment lines, removing the space that follows `///`.
+fn doc_comment(lines: &[String]) -> Option<String> {
+    let doc = lines
+        .iter()
+        .map(|l| l.strip_prefix(' ').unwrap_or(l).trim_end())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let doc = doc.trim();
+    (!doc.is_empty()).then(|| doc.to_string())
+}
+
+fn option_tokens(value: Option<impl ToTokens>) -> TokenStream {
+    match value {
+        Some(value) => quote!(Some(#value)),
+        None => quote!(None),
+    }
+}
+
+/// Generates the code for the type's data model, reusing the struct size calculation.
+fn model_tokens(
+    item: &DeriveInput,
+    model_generators: TokenStream,
+    extra_size: Option<TokenStream>,
+) -> TokenStream {
+    let name = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+    quote! {
         impl #impl_generics recordkeeper_data_model::Model for #name #ty_generics #where_clause {
             fn model() -> recordkeeper_data_model::TypeModel {
                 let mut current_loc: usize = 0;
@@ -275,7 +391,241 @@ pub fn derive_save_deserialize(item: proc_macro::TokenStream) -> proc_macro::Tok
                 recordkeeper_data_model::TypeModel::Struct(Box::new(__BUILDER.build()))
             }
         }
-    };
+    }
+}
 
-    out.into()
+/// Generates `SerdeBin`, `Serialize` and `Deserialize` implementations, gated
+/// behind the library's `serde` feature.
+fn serde_tokens(name: &Ident, generics: &Generics, fields: &[FieldVisitor]) -> TokenStream {
+    let field_names: Vec<_> = fields
+        .iter()
+        .map(|v| v.field.ident.as_ref().unwrap())
+        .collect();
+    let field_types: Vec<_> = fields.iter().map(|v| &v.field.ty).collect();
+    let field_indices: Vec<_> = (0..fields.len()).collect();
+    let field_vars: Vec<_> = field_indices
+        .iter()
+        .map(|i| format_ident!("__field{i}"))
+        .collect();
+    let field_count = fields.len();
+    let assert_checks: Vec<_> = fields
+        .iter()
+        .filter_map(|v| v.serde_assert_tokens(name))
+        .collect();
+
+    // Every field type must also be (de)serializable
+    let mut generics = generics.clone();
+    let where_clause = generics.make_where_clause();
+    for ty in &field_types {
+        where_clause
+            .predicates
+            .push(parse_quote!(#ty: ::recordkeeper::io::serde::SerdeBin));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let mut de_generics = generics.clone();
+    de_generics.params.insert(0, parse_quote!('de));
+    let (de_impl_generics, _, _) = de_generics.split_for_impl();
+
+    quote! {
+        #[cfg(feature = "serde")]
+        const _: () = {
+            use ::serde::de::{Error as _, IgnoredAny, MapAccess, SeqAccess, Visitor};
+            use ::serde::ser::SerializeStruct;
+            use ::recordkeeper::io::serde::{FieldSeed, SerdeBin, Ser, Seed};
+
+            const __FIELDS: &[&str] = &[#(stringify!(#field_names)),*];
+
+            struct __Visitor #impl_generics (std::marker::PhantomData<#name #ty_generics>) #where_clause;
+
+            impl #de_impl_generics Visitor<'de> for __Visitor #ty_generics #where_clause {
+                type Value = #name #ty_generics;
+
+                fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    formatter.write_str(concat!("struct ", stringify!(#name)))
+                }
+
+                fn visit_seq<__A: SeqAccess<'de>>(self, mut __seq: __A) -> Result<Self::Value, __A::Error> {
+                    #(
+                        let #field_names = __seq
+                            .next_element_seed(Seed::<#field_types>::new())?
+                            .ok_or_else(|| __A::Error::invalid_length(#field_indices, &self))?;
+                    )*
+                    #(#assert_checks)*
+                    Ok(#name { #(#field_names),* })
+                }
+
+                fn visit_map<__A: MapAccess<'de>>(self, mut __map: __A) -> Result<Self::Value, __A::Error> {
+                    #(let mut #field_vars: Option<#field_types> = None;)*
+                    while let Some(__key) = __map.next_key_seed(FieldSeed(__FIELDS))? {
+                        match __key {
+                            #(
+                                Some(#field_indices) => {
+                                    if #field_vars.is_some() {
+                                        return Err(__A::Error::duplicate_field(stringify!(#field_names)));
+                                    }
+                                    #field_vars = Some(__map.next_value_seed(Seed::<#field_types>::new())?);
+                                }
+                            )*
+                            _ => {
+                                __map.next_value::<IgnoredAny>()?;
+                            }
+                        }
+                    }
+                    #(
+                        let #field_names = #field_vars
+                            .ok_or_else(|| __A::Error::missing_field(stringify!(#field_names)))?;
+                    )*
+                    #(#assert_checks)*
+                    Ok(#name { #(#field_names),* })
+                }
+            }
+
+            impl #impl_generics SerdeBin for #name #ty_generics #where_clause {
+                fn serialize<__S: ::serde::Serializer>(&self, __serializer: __S) -> Result<__S::Ok, __S::Error> {
+                    let mut __state = __serializer.serialize_struct(stringify!(#name), #field_count)?;
+                    #(__state.serialize_field(stringify!(#field_names), &Ser(&self.#field_names))?;)*
+                    __state.end()
+                }
+
+                fn deserialize<'de, __D: ::serde::Deserializer<'de>>(__deserializer: __D) -> Result<Self, __D::Error> {
+                    __deserializer.deserialize_struct(
+                        stringify!(#name),
+                        __FIELDS,
+                        __Visitor(std::marker::PhantomData),
+                    )
+                }
+            }
+
+            impl #impl_generics ::serde::Serialize for #name #ty_generics #where_clause {
+                fn serialize<__S: ::serde::Serializer>(&self, __serializer: __S) -> Result<__S::Ok, __S::Error> {
+                    SerdeBin::serialize(self, __serializer)
+                }
+            }
+
+            impl #de_impl_generics ::serde::Deserialize<'de> for #name #ty_generics #where_clause {
+                fn deserialize<__D: ::serde::Deserializer<'de>>(__deserializer: __D) -> Result<Self, __D::Error> {
+                    SerdeBin::deserialize(__deserializer)
+                }
+            }
+        };
+    }
+}
+
+/// Generates the implementations for fieldless enums, which are stored as their
+/// discriminant.
+///
+/// The enum must have a `#[repr]` attribute with an unsigned integer type.
+fn enum_tokens(item: &DeriveInput, data: &DataEnum) -> TokenStream {
+    let name = &item.ident;
+    let repr = item
+        .attrs
+        .iter()
+        .find(|a| a.path().is_ident("repr"))
+        .and_then(|a| a.parse_args::<Ident>().ok())
+        .filter(|r| ["u8", "u16", "u32", "u64"].iter().any(|t| r == t))
+        .expect("SaveBin enums need an unsigned integer repr, e.g. #[repr(u32)]");
+    if !item.generics.params.is_empty() {
+        panic!("SaveBin can't be derived on generic enums");
+    }
+    let variants: Vec<_> = data
+        .variants
+        .iter()
+        .map(|v| match v.fields {
+            Fields::Unit => &v.ident,
+            _ => panic!("SaveBin can only be derived on fieldless enums"),
+        })
+        .collect();
+    let variant_indices: Vec<_> = (0..variants.len() as u32).collect();
+    let variant_positions: Vec<_> = (0..variants.len()).collect();
+
+    quote! {
+        impl TryFrom<#repr> for #name {
+            type Error = ::recordkeeper::error::DiscriminantError;
+
+            fn try_from(value: #repr) -> Result<Self, Self::Error> {
+                #(
+                    if value == Self::#variants as #repr {
+                        return Ok(Self::#variants);
+                    }
+                )*
+                Err(::recordkeeper::error::DiscriminantError {
+                    enum_name: stringify!(#name),
+                    value: value.into(),
+                })
+            }
+        }
+
+        impl ::recordkeeper::io::SaveBin for #name {
+            type ReadError = ::recordkeeper::error::SaveError;
+            type WriteError = ::recordkeeper::error::SaveError;
+
+            fn read(__IN_BYTES: &mut std::io::Cursor::<&[u8]>) -> Result<Self, Self::ReadError> {
+                let value = <#repr as ::recordkeeper::io::SaveBin>::read(__IN_BYTES)?;
+                Ok(Self::try_from(value)?)
+            }
+
+            fn write(&self, __OUT_BYTES: &mut [u8]) -> Result<(), Self::WriteError> {
+                let value = match self {
+                    #(Self::#variants => Self::#variants as #repr,)*
+                };
+                ::recordkeeper::io::SaveBin::write(&value, __OUT_BYTES)
+            }
+
+            const SIZE: usize = <#repr as ::recordkeeper::io::SaveBin>::SIZE;
+        }
+
+        impl recordkeeper_data_model::Model for #name {
+            fn model() -> recordkeeper_data_model::TypeModel {
+                recordkeeper_data_model::TypeModel::Enum(Box::new(recordkeeper_data_model::EnumModel {
+                    name: stringify!(#name),
+                    type_id: std::any::TypeId::of::<Self>(),
+                    repr: stringify!(#repr),
+                    variants: vec![#((stringify!(#variants), Self::#variants as u64)),*],
+                }))
+            }
+        }
+
+        // Enums are (de)serialized as their variant name
+        #[cfg(feature = "serde")]
+        const _: () = {
+            use ::serde::de::{DeserializeSeed, Error as _};
+            use ::recordkeeper::io::serde::{FieldSeed, SerdeBin};
+
+            const __VARIANTS: &[&str] = &[#(stringify!(#variants)),*];
+
+            impl SerdeBin for #name {
+                fn serialize<__S: ::serde::Serializer>(&self, __serializer: __S) -> Result<__S::Ok, __S::Error> {
+                    match self {
+                        #(
+                            Self::#variants => __serializer.serialize_unit_variant(
+                                stringify!(#name),
+                                #variant_indices,
+                                stringify!(#variants),
+                            ),
+                        )*
+                    }
+                }
+
+                fn deserialize<'de, __D: ::serde::Deserializer<'de>>(__deserializer: __D) -> Result<Self, __D::Error> {
+                    match FieldSeed(__VARIANTS).deserialize(__deserializer)? {
+                        #(Some(#variant_positions) => Ok(Self::#variants),)*
+                        _ => Err(__D::Error::custom(concat!("unknown variant for enum ", stringify!(#name)))),
+                    }
+                }
+            }
+
+            impl ::serde::Serialize for #name {
+                fn serialize<__S: ::serde::Serializer>(&self, __serializer: __S) -> Result<__S::Ok, __S::Error> {
+                    SerdeBin::serialize(self, __serializer)
+                }
+            }
+
+            impl<'de> ::serde::Deserialize<'de> for #name {
+                fn deserialize<__D: ::serde::Deserializer<'de>>(__deserializer: __D) -> Result<Self, __D::Error> {
+                    SerdeBin::deserialize(__deserializer)
+                }
+            }
+        };
+    }
 }
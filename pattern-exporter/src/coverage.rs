@@ -0,0 +1,51 @@
+use std::path::PathBuf;
+
+use recordkeeper_data_model::StructModel;
+
+/// Prints the unmapped byte ranges of the model, along with the number of
+/// corpus files where each range is non-zero.
+pub fn export_coverage(root: &StructModel, corpus: &[PathBuf]) {
+    let files: Vec<_> = corpus
+        .iter()
+        .map(|path| {
+            let bytes = std::fs::read(path)
+                .unwrap_or_else(|e| panic!("could not read {}: {e}", path.display()));
+            if bytes.len() != root.total_len {
+                eprintln!(
+                    "warning: {} is 0x{:x} bytes long, expected 0x{:x}",
+                    path.display(),
+                    bytes.len(),
+                    root.total_len
+                );
+            }
+            bytes
+        })
+        .collect();
+
+    let ranges = root.unmapped_ranges();
+    let total: usize = ranges.iter().map(|r| r.total_size()).sum();
+    println!(
+        "{} unmapped ranges, {total} of {} bytes",
+        ranges.len(),
+        root.total_len
+    );
+
+    for range in &ranges {
+        let parent = match range.path.as_str() {
+            "" => range.parent.to_string(),
+            path => format!("{} ({path})", range.parent),
+        };
+        print!(
+            "0x{:06x}  {parent} +0x{:x}, {} bytes",
+            range.offsets[0], range.relative_offset, range.size
+        );
+        if range.offsets.len() > 1 {
+            print!(" x{}", range.offsets.len());
+        }
+        if !files.is_empty() {
+            let non_zero = files.iter().filter(|f| range.is_non_zero(f)).count();
+            print!(", non-zero in {non_zero}/{} files", files.len());
+        }
+        println!();
+    }
+}
@@ -1,9 +1,9 @@
 use std::{
-    collections::{HashSet, VecDeque},
+    collections::{BTreeMap, HashSet, VecDeque},
     io::{Result, Write},
 };
 
-use recordkeeper_data_model::{StructModel, TypeModel};
+use recordkeeper_data_model::{EnumModel, FieldAnnotations, FlagsModel, StructModel, TypeModel};
 
 use crate::UniqueTypeName;
 
@@ -11,13 +11,15 @@ pub fn export_imhex(root: &StructModel) {
     let mut structs = VecDeque::new();
     structs.push_back(root);
     let mut written = HashSet::new();
+    // Enums and bitfields, which must be declared before use
+    let mut types = BTreeMap::new();
 
     let mut out = Vec::new();
 
     while let Some(str) = structs.pop_back() {
         let name = UniqueTypeName::from((str.name, str.type_id));
         if written.insert(name.clone()) {
-            write_struct(&mut out, str, &name, &mut structs).unwrap();
+            write_struct(&mut out, str, &name, &mut structs, &mut types).unwrap();
         }
     }
 
@@ -35,6 +37,15 @@ pub fn export_imhex(root: &StructModel) {
     // For simpler multi-dim array support
     println!(r#"struct Array<T, auto size> {{ T data[size] [[inline]]; }};"#);
 
+    // Templates for types with special models
+    println!(r#"struct FixVec<T, auto capacity, L> {{ T items[capacity]; L len; }};"#);
+    println!(r#"struct FixStr<auto capacity, L> {{ char str[capacity]; L len; }};"#);
+    println!(r#"struct BitFlags<auto bits, auto words> {{ u32 words[words]; }};"#);
+
+    for decl in types.values() {
+        print!("{decl}");
+    }
+
     // Forward declaration for all defined structs
     for str in written {
         println!("using {str};");
@@ -55,6 +66,7 @@ fn write_struct<'a>(
     model: &'a StructModel,
     name: &UniqueTypeName,
     structs: &mut VecDeque<&'a StructModel>,
+    types: &mut BTreeMap<UniqueTypeName, String>,
 ) -> Result<()> {
     writeln!(&mut writer, "struct {} {{", name)?;
 
@@ -66,22 +78,15 @@ fn write_struct<'a>(
         offset = field.offset + field.size;
         match &field.type_model {
             TypeModel::Empty => continue,
-            TypeModel::Struct(s) => structs.push_back(s.as_ref()),
-            TypeModel::Array(arr) => {
-                if let TypeModel::Empty = &arr.0 {
-                    continue;
-                }
-                if let TypeModel::Struct(s) = &arr.0 {
-                    structs.push_back(s.as_ref())
-                }
-            }
-            _ => {}
+            TypeModel::Array(arr) if matches!(arr.0, TypeModel::Empty) => continue,
+            ty => add_type(ty, structs, types),
         }
 
         writeln!(
             &mut writer,
-            "    {};",
+            "    {}{};",
             fmt_field(&field.type_model, field.name),
+            fmt_comment(&field.annotations),
         )?;
     }
 
@@ -93,12 +98,92 @@ fn write_struct<'a>(
     Ok(())
 }
 
+/// Queues struct types for writing, and declares enums and bitfields.
+fn add_type<'a>(
+    ty: &'a TypeModel,
+    structs: &mut VecDeque<&'a StructModel>,
+    types: &mut BTreeMap<UniqueTypeName, String>,
+) {
+    match ty {
+        TypeModel::Struct(s) => structs.push_back(s.as_ref()),
+        TypeModel::Array(arr) => add_type(&arr.0, structs, types),
+        TypeModel::Vec(vec) => add_type(&vec.item, structs, types),
+        TypeModel::Enum(e) => {
+            types
+                .entry(UniqueTypeName::from((e.name, e.type_id)))
+                .or_insert_with_key(|name| fmt_enum(name, e));
+        }
+        TypeModel::Flags(f) => {
+            types
+                .entry(UniqueTypeName::from((f.name, f.type_id)))
+                .or_insert_with_key(|name| fmt_bitfield(name, f));
+        }
+        TypeModel::Empty
+        | TypeModel::Primitive(_)
+        | TypeModel::String(_)
+        | TypeModel::BitFlags(_) => {}
+    }
+}
+
+fn fmt_enum(name: &UniqueTypeName, model: &EnumModel) -> String {
+    let mut out = format!("enum {name} : {} {{\n", model.repr);
+    for (variant, discriminant) in &model.variants {
+        out += &format!("    {variant} = {discriminant},\n");
+    }
+    out + "};\n"
+}
+
+fn fmt_bitfield(name: &UniqueTypeName, model: &FlagsModel) -> String {
+    let mut flags = model.flags.clone();
+    flags.sort_by_key(|(_, index)| *index);
+
+    let mut out = format!("bitfield {name} {{\n");
+    let mut bit = 0;
+    for (flag, index) in flags {
+        if index > bit {
+            out += &format!("    padding : {};\n", index - bit);
+        }
+        out += &format!("    {flag} : 1;\n");
+        bit = index + 1;
+    }
+    let total_bits = model.storage.size() * 8;
+    if total_bits > bit {
+        out += &format!("    padding : {};\n", total_bits - bit);
+    }
+    out + "};\n"
+}
+
+/// Shows field documentation in ImHex's pattern data view.
+fn fmt_comment(annotations: &FieldAnnotations) -> String {
+    let mut lines = Vec::new();
+    lines.extend(annotations.doc.map(str::to_string));
+    if let Some(table) = annotations.table {
+        lines.push(format!("Table: {table}"));
+    }
+    if let Some(range) = &annotations.range {
+        lines.push(format!("Range: {}-{}", range.start(), range.end()));
+    }
+    if lines.is_empty() {
+        return String::new();
+    }
+    let comment = lines
+        .join("\n")
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!(" [[comment(\"{comment}\")]]")
+}
+
 fn fmt_field(ty: &TypeModel, name: &str) -> String {
     match ty {
         TypeModel::Empty => format!("char {name}[0]"),
         TypeModel::Struct(s) => format!("{} {name}", UniqueTypeName::from((s.name, s.type_id))),
         TypeModel::Array(arr) => format!("Array<{}, {}> {}", fmt_naked_type(&arr.0), arr.1, name),
         TypeModel::Primitive(t) => format!("{t} {name}"),
+        TypeModel::Enum(e) => format!("{} {name}", UniqueTypeName::from((e.name, e.type_id))),
+        TypeModel::Flags(f) => format!("{} {name}", UniqueTypeName::from((f.name, f.type_id))),
+        TypeModel::String(s) if s.len_type.is_none() => format!("char {name}[{}]", s.capacity),
+        ty => format!("{} {name}", fmt_naked_type(ty)),
     }
 }
 
@@ -108,5 +193,18 @@ fn fmt_naked_type(ty: &TypeModel) -> String {
         TypeModel::Struct(s) => UniqueTypeName::from((s.name, s.type_id)).to_string(),
         TypeModel::Array(arr) => format!("Array<{}, {}>", fmt_naked_type(&arr.0), arr.1),
         TypeModel::Primitive(t) => t.to_string(),
+        TypeModel::Enum(e) => UniqueTypeName::from((e.name, e.type_id)).to_string(),
+        TypeModel::Flags(f) => UniqueTypeName::from((f.name, f.type_id)).to_string(),
+        TypeModel::Vec(v) => format!(
+            "FixVec<{}, {}, {}>",
+            fmt_naked_type(&v.item),
+            v.capacity,
+            v.len_type
+        ),
+        TypeModel::String(s) => match s.len_type {
+            Some(len_type) => format!("FixStr<{}, {len_type}>", s.capacity),
+            None => format!("Array<char, {}>", s.capacity),
+        },
+        TypeModel::BitFlags(b) => format!("BitFlags<{}, {}>", b.bits, b.words),
     }
 }
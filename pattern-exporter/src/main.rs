@@ -3,12 +3,14 @@ use std::{
     collections::hash_map::DefaultHasher,
     fmt::Display,
     hash::{Hash, Hasher},
+    path::PathBuf,
 };
 
 use clap::{Parser, ValueEnum};
 use recordkeeper::{SaveData, SystemData};
 use recordkeeper_data_model::{Model, ModelName, TypeModel};
 
+mod coverage;
 mod imhex;
 
 #[derive(Parser)]
@@ -18,12 +20,16 @@ pub struct Cli {
     output: OutputFormat,
     #[arg(value_enum)]
     file: FileType,
+    /// Files to check for non-zero unmapped bytes (coverage output only)
+    corpus: Vec<PathBuf>,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 #[clap(rename_all = "lowercase")]
 enum OutputFormat {
     ImHex,
+    /// Byte ranges that are not mapped to any field
+    Coverage,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -48,6 +54,7 @@ fn main() {
 
     match cli.output {
         OutputFormat::ImHex => imhex::export_imhex(&model),
+        OutputFormat::Coverage => coverage::export_coverage(&model, &cli.corpus),
     }
 }
 
@@ -0,0 +1,5 @@
+
+    use unic_langid::{langid, LanguageIdentifier};
+
+    pub const LANGUAGE_FILES: [(LanguageIdentifier, &str); 9] = [ (langid!("en-US"), include_str!("/tmp/base/webapp/lang/en_us.ftl")),  (langid!("jp"), include_str!("/tmp/base/webapp/lang/jp.ftl")),  (langid!("zh-Hans"), include_str!("/tmp/base/webapp/lang/zh-Hans.ftl")),  (langid!("zh-Hant"), include_str!("/tmp/base/webapp/lang/zh-Hant.ftl")),  (langid!("ko"), include_str!("/tmp/base/webapp/lang/ko.ftl")),  (langid!("de"), include_str!("/tmp/base/webapp/lang/de.ftl")),  (langid!("es"), include_str!("/tmp/base/webapp/lang/es.ftl")),  (langid!("fr"), include_str!("/tmp/base/webapp/lang/fr.ftl")),  (langid!("it"), include_str!("/tmp/base/webapp/lang/it.ftl")), ];    
+    
\ No newline at end of file